@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant};
+
+use aoc_2019::intcode::{Computer, DenseMemory, Memory, SparseMemory};
+
+// probes the day 19 tractor beam over a 50x50 grid, resetting the computer for every point
+fn tractor_beam<M: Memory>(program: &[i64]) -> i64 {
+    let mut computer: Computer<M> = Computer::with_memory(program, &[]);
+    let mut affected = 0;
+    for y in 0..50 {
+        for x in 0..50 {
+            computer.reset();
+            computer.add_input(&[x, y]);
+            affected += computer.next().unwrap();
+        }
+    }
+    affected
+}
+
+// day 9 part two, a single long run that mostly works in the relative base region
+fn sensor_boost<M: Memory>(program: &[i64]) -> i64 {
+    let mut computer: Computer<M> = Computer::with_memory(program, &[2]);
+    computer.next().unwrap()
+}
+
+fn sensor_boost_uncached(program: &[i64]) -> i64 {
+    let mut computer: Computer = Computer::with_memory(program, &[2]);
+    computer.set_decode_cache(false);
+    computer.next().unwrap()
+}
+
+fn time<F: FnMut() -> i64>(iterations: u32, mut f: F) -> (Duration, i64) {
+    let start = Instant::now();
+    let mut result = 0;
+    for _ in 0..iterations {
+        result = f();
+    }
+    (start.elapsed() / iterations, result)
+}
+
+fn compare(
+    name: &str,
+    iterations: u32,
+    (before, slow): (&str, impl FnMut() -> i64),
+    (after, fast): (&str, impl FnMut() -> i64),
+) {
+    let (slow, a) = time(iterations, slow);
+    let (fast, b) = time(iterations, fast);
+    assert_eq!(a, b, "{}: results disagree", name);
+    println!(
+        "{:<14} {} {:>10.3?}  {} {:>10.3?}  speedup {:.2}x",
+        name,
+        before,
+        slow,
+        after,
+        fast,
+        slow.as_secs_f64() / fast.as_secs_f64()
+    );
+}
+
+fn main() {
+    let day_19 = Computer::load_data(19).unwrap();
+    compare(
+        "day 19 part 1",
+        5,
+        ("sparse", || tractor_beam::<SparseMemory>(&day_19)),
+        ("dense", || tractor_beam::<DenseMemory>(&day_19)),
+    );
+    let day_9 = Computer::load_data(9).unwrap();
+    compare(
+        "day 9 part 2",
+        5,
+        ("sparse", || sensor_boost::<SparseMemory>(&day_9)),
+        ("dense", || sensor_boost::<DenseMemory>(&day_9)),
+    );
+    compare(
+        "day 9 part 2",
+        5,
+        ("uncached", || sensor_boost_uncached(&day_9)),
+        ("cached", || sensor_boost::<DenseMemory>(&day_9)),
+    );
+}
@@ -1,40 +1,30 @@
+use super::intcode::patch::Patch;
+use super::intcode::symbolic::{Symbolic, Var};
 use super::intcode::Computer;
 
 pub fn solve_part_one() -> i64 {
-    let program = {
-        let mut x = Computer::load_data(2);
-        x[1] = 12;
-        x[2] = 2;
-        x
-    };
-    let mut computer = Computer::new(&program, &[]);
+    let mut computer = Computer::new(&Computer::load_data(2).unwrap(), &[]);
+    computer
+        .apply_patch(&Patch::new("1202 program alarm").set(1, 12).set(2, 2))
+        .unwrap();
     computer.next();
-    computer.program[&0]
+    computer.peek(0)
 }
 
+// the output is linear in the noun and verb, so solve for them instead of trying every pair
 pub fn solve_part_two() -> Option<i64> {
-    let program = Computer::load_data(2);
-    for noun in 0i64..100i64 {
-        for verb in 0i64..100i64 {
-            let program = {
-                let mut cloned = program.clone();
-                cloned[1] = noun;
-                cloned[2] = verb;
-                cloned
-            };
-            let mut computer = Computer::new(&program, &[]);
-            computer.next();
-            if computer.program[&0] == 19690720 {
-                return Option::Some(100 * noun + verb);
-            }
-        }
-    }
-    Option::None
+    let assignment = Symbolic::new(&Computer::load_data(2).unwrap())
+        .with_cell(1, 0..=99)
+        .with_cell(2, 0..=99)
+        .solve(0, 19690720)
+        .unwrap()?;
+    Some(100 * assignment[&Var::Cell(1)] + assignment[&Var::Cell(2)])
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::intcode::patch::Search;
 
     #[test]
     fn test_run_opcodes() {
@@ -66,4 +56,18 @@ mod tests {
     fn test_solve_part_two() {
         assert_eq!(solve_part_two().unwrap(), 100 * 77 + 33);
     }
+
+    #[test]
+    fn test_search_agrees() {
+        // trying the pairs around the answer finds the same noun and verb
+        let found = Search::new(&Computer::load_data(2).unwrap())
+            .sweep(1, 70..=79)
+            .sweep(2, 30..=39)
+            .find(|outcome| outcome.peek(0) == 19690720)
+            .unwrap();
+        assert_eq!(
+            (found.patch.get(1), found.patch.get(2)),
+            (Some(77), Some(33))
+        );
+    }
 }
@@ -1,13 +1,117 @@
-use std::collections::HashSet;
-
+use super::intcode::circuit::Circuit;
+use super::intcode::parallel;
 use super::intcode::Computer;
 
-fn all_unique_settings(x: &Vec<i64>) -> bool {
-    let items: HashSet<i64> = x.into_iter().cloned().collect();
-    items.len() == x.len()
+// every ordering of `count` distinct phases picked from `phases`, in lexicographic order of
+// their positions in `phases`
+pub struct Permutations {
+    phases: Vec<i64>,
+    count: usize,
+    indices: Vec<usize>,
+    used: Vec<bool>,
+    done: bool,
+}
+
+impl Permutations {
+    pub fn new(phases: &[i64], count: usize) -> Self {
+        let mut permutations = Permutations {
+            phases: phases.to_vec(),
+            count,
+            indices: Vec::with_capacity(count),
+            used: vec![false; phases.len()],
+            done: count > phases.len(),
+        };
+        permutations.fill();
+        permutations
+    }
+    // extends the current prefix with the smallest unused indices
+    fn fill(&mut self) {
+        for i in 0..self.used.len() {
+            if self.indices.len() == self.count {
+                break;
+            }
+            if !self.used[i] {
+                self.used[i] = true;
+                self.indices.push(i);
+            }
+        }
+    }
+    // moves to the next ordering, returns false once they have all been seen
+    fn advance(&mut self) -> bool {
+        while let Some(last) = self.indices.pop() {
+            self.used[last] = false;
+            if let Some(next) = (last + 1..self.used.len()).find(|&i| !self.used[i]) {
+                self.used[next] = true;
+                self.indices.push(next);
+                self.fill();
+                return true;
+            }
+        }
+        false
+    }
 }
 
-fn run_amplifiers(program: &Vec<i64>, settings: &Vec<i64>) -> i64 {
+impl Iterator for Permutations {
+    type Item = Vec<i64>;
+
+    fn next(&mut self) -> Option<Vec<i64>> {
+        if self.done {
+            return None;
+        }
+        let settings = self.indices.iter().map(|&i| self.phases[i]).collect();
+        self.done = !self.advance();
+        Some(settings)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Mode {
+    Exhaustive,
+    // stop as soon as some settings produce at least this output
+    Target(i64),
+    // stop after trying this many settings
+    Limit(usize),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Scored {
+    pub settings: Vec<i64>,
+    pub output: i64,
+}
+
+// tries settings for `amplifiers` amplifiers and returns the best `keep` of them, highest output
+// first. ties keep the order the settings were tried in
+pub fn search<F: FnMut(&[i64]) -> i64>(
+    phases: &[i64],
+    amplifiers: usize,
+    keep: usize,
+    mode: Mode,
+    mut evaluate: F,
+) -> Vec<Scored> {
+    let mut best: Vec<Scored> = Vec::with_capacity(keep + 1);
+    for (tried, settings) in Permutations::new(phases, amplifiers).enumerate() {
+        if let Mode::Limit(limit) = mode {
+            if tried == limit {
+                break;
+            }
+        }
+        let output = evaluate(&settings);
+        let rank = best.iter().position(|scored| scored.output < output);
+        let rank = rank.unwrap_or(best.len());
+        if rank < keep {
+            best.insert(rank, Scored { settings, output });
+            best.truncate(keep);
+        }
+        if let Mode::Target(target) = mode {
+            if output >= target {
+                break;
+            }
+        }
+    }
+    best
+}
+
+pub fn run_amplifiers(program: &Vec<i64>, settings: &[i64]) -> i64 {
     let mut output: Vec<i64> = vec![0];
     for &setting in settings {
         let input = vec![setting, *output.first().unwrap()];
@@ -17,7 +121,7 @@ fn run_amplifiers(program: &Vec<i64>, settings: &Vec<i64>) -> i64 {
     *output.first().unwrap()
 }
 
-fn run_amplifiers_with_feedback(program: &Vec<i64>, settings: &Vec<i64>) -> i64 {
+pub fn run_amplifiers_with_feedback(program: &Vec<i64>, settings: &[i64]) -> i64 {
     let mut computers: Vec<Computer> = settings
         .iter()
         .map(|&setting| Computer::new(program, &[setting]))
@@ -36,50 +140,134 @@ fn run_amplifiers_with_feedback(program: &Vec<i64>, settings: &Vec<i64>) -> i64
     last_output
 }
 
+// same as above with every amplifier on its own thread
+fn run_amplifiers_threaded(program: &[i64], settings: &[i64]) -> i64 {
+    let mut circuit = Circuit::ring(program, settings);
+    circuit.add_input(0, &[0]);
+    let outputs = circuit.run().unwrap();
+    *outputs.last().unwrap().last().unwrap()
+}
+
 fn maximum_input_combination(program: &Vec<i64>) -> i64 {
-    iproduct!(0..5, 0..5, 0..5, 0..5, 0..5)
-        .into_iter()
-        .map(|(a, b, c, d, e)| vec![a, b, c, d, e])
-        .filter(all_unique_settings)
-        .map(|settings| run_amplifiers(&program, &settings))
-        .max()
-        .unwrap()
+    search(&[0, 1, 2, 3, 4], 5, 1, Mode::Exhaustive, |settings| {
+        run_amplifiers(program, settings)
+    })[0]
+        .output
 }
 
-fn maximum_input_combination_feedback(program: &Vec<i64>) -> i64 {
-    iproduct!(5..10, 5..10, 5..10, 5..10, 5..10)
-        .into_iter()
-        .map(|(a, b, c, d, e)| vec![a, b, c, d, e])
-        .filter(all_unique_settings)
-        .map(|settings| run_amplifiers_with_feedback(&program, &settings))
-        .max()
-        .unwrap()
+fn maximum_input_combination_feedback(program: &Vec<i64>, parallel: bool) -> i64 {
+    let phases = [5, 6, 7, 8, 9];
+    if !parallel {
+        return search(&phases, 5, 1, Mode::Exhaustive, |settings| {
+            run_amplifiers_with_feedback(program, settings)
+        })[0]
+            .output;
+    }
+    let permutations: Vec<Vec<i64>> = Permutations::new(&phases, 5).collect();
+    parallel::map_indices(permutations.len(), parallel::workers(), |i| {
+        Some(run_amplifiers_threaded(program, &permutations[i]))
+    })
+    .into_iter()
+    .max()
+    .unwrap()
 }
 
 pub fn solve_part_one() -> i64 {
-    let program = Computer::load_data(7);
+    let program = Computer::load_data(7).unwrap();
     maximum_input_combination(&program)
 }
 
 pub fn solve_part_two() -> i64 {
-    let program = Computer::load_data(7);
-    maximum_input_combination_feedback(&program)
+    let program = Computer::load_data(7).unwrap();
+    maximum_input_combination_feedback(&program, false)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::intcode::asm::assemble;
+
+    #[test]
+    fn test_permutations() {
+        assert_eq!(
+            Permutations::new(&[1, 2, 3], 2).collect::<Vec<Vec<i64>>>(),
+            vec![
+                vec![1, 2],
+                vec![1, 3],
+                vec![2, 1],
+                vec![2, 3],
+                vec![3, 1],
+                vec![3, 2]
+            ]
+        );
+        assert_eq!(Permutations::new(&[0, 1, 2, 3, 4], 5).count(), 120);
+        assert_eq!(Permutations::new(&[0, 1, 2, 3, 4, 5, 6], 3).count(), 210);
+        assert_eq!(
+            Permutations::new(&[1, 2], 0).collect::<Vec<Vec<i64>>>(),
+            vec![vec![]]
+        );
+        assert_eq!(Permutations::new(&[1, 2], 3).count(), 0);
+    }
+
+    #[test]
+    fn test_search() {
+        let program = Computer::load_data(7).unwrap();
+        let best = search(&[0, 1, 2, 3, 4], 5, 3, Mode::Exhaustive, |settings| {
+            run_amplifiers(&program, settings)
+        });
+        assert_eq!(best.len(), 3);
+        assert_eq!(best[0].output, 21860);
+        assert!(best.windows(2).all(|pair| pair[0].output >= pair[1].output));
+        assert_eq!(run_amplifiers(&program, &best[1].settings), best[1].output);
+
+        let mut tried = 0;
+        let found = search(&[0, 1, 2, 3, 4], 5, 1, Mode::Target(21860), |settings| {
+            tried += 1;
+            run_amplifiers(&program, settings)
+        });
+        assert_eq!(found[0], best[0]);
+        assert!(tried < 120);
+
+        let mut tried = 0;
+        search(&[0, 1, 2, 3, 4, 5, 6], 7, 2, Mode::Limit(10), |settings| {
+            tried += 1;
+            run_amplifiers(&program, settings)
+        });
+        assert_eq!(tried, 10);
+    }
 
     #[test]
     fn test_maximum_input_combination() {
-        let values: Vec<(Vec<i64>, i64)> = vec![
+        let values: Vec<(&str, Vec<i64>, i64)> = vec![
             (
+                "
+                    in phase
+                    in signal
+                    mul signal, #10, signal
+                    add signal, phase, phase
+                    out phase
+                    hlt
+                phase:  db 0
+                signal: db 0
+                ",
                 vec![
                     3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
                 ],
                 43210,
             ),
             (
+                "
+                    in phase
+                    in signal
+                    mul signal, #10, signal
+                    mul phase, #-1, phase
+                    add #5, phase, phase
+                    add signal, phase, phase
+                    out phase
+                    hlt
+                phase:  db 0
+                signal: db 0
+                ",
                 vec![
                     3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23,
                     23, 4, 23, 99, 0, 0,
@@ -87,6 +275,21 @@ mod tests {
                 54321,
             ),
             (
+                "
+                    in phase
+                    in signal
+                    mul signal, #10, signal
+                    add phase, #-2, phase
+                    lt phase, #0, negative
+                    mul negative, #7, negative
+                    add negative, phase, phase
+                    add signal, phase, phase
+                    out phase
+                    hlt
+                phase:    db 0
+                signal:   db 0
+                negative: db 0
+                ",
                 vec![
                     3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7,
                     33, 1, 33, 31, 31, 1, 32, 31, 31, 4, 31, 99, 0, 0, 0,
@@ -94,19 +297,43 @@ mod tests {
                 65210,
             ),
         ];
-        for (program, output) in values {
+        for (text, example, output) in values {
+            let program = assemble(text).unwrap();
+            assert_eq!(program, example);
             assert_eq!(maximum_input_combination(&program), output);
         }
     }
 
     #[test]
     fn test_run_amplifiers_with_feedback() {
-        let program = vec![
-            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
-            28, 1005, 28, 6, 99, 0, 0, 5,
-        ];
+        let program = assemble(
+            "
+                in phase
+                add phase, #-4, phase
+            loop:
+                in signal
+                mul signal, #2, signal
+                add signal, phase, signal
+                out signal
+                add count, #-1, count
+                jnz count, #loop
+                hlt
+            phase:  db 0
+            signal: db 0
+            count:  db 5
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            program,
+            vec![
+                3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28,
+                -1, 28, 1005, 28, 6, 99, 0, 0, 5,
+            ]
+        );
         let settings = vec![9, 8, 7, 6, 5];
         assert_eq!(run_amplifiers_with_feedback(&program, &settings), 139629729);
+        assert_eq!(run_amplifiers_threaded(&program, &settings), 139629729);
     }
 
     #[test]
@@ -129,7 +356,8 @@ mod tests {
             ),
         ];
         for (program, output) in values {
-            assert_eq!(maximum_input_combination_feedback(&program), output);
+            assert_eq!(maximum_input_combination_feedback(&program, false), output);
+            assert_eq!(maximum_input_combination_feedback(&program, true), output);
         }
     }
 
@@ -142,4 +370,10 @@ mod tests {
     fn test_solve_part_two() {
         assert_eq!(solve_part_two(), 2645740);
     }
+
+    #[test]
+    fn test_solve_part_two_in_parallel() {
+        let program = Computer::load_data(7).unwrap();
+        assert_eq!(maximum_input_combination_feedback(&program, true), 2645740);
+    }
 }
@@ -0,0 +1,41 @@
+use std::env;
+use std::io;
+use std::process;
+
+use aoc_2019::intcode::debugger::{repl, Debugger};
+use aoc_2019::intcode::patch::Patch;
+use aoc_2019::intcode::Computer;
+
+const USAGE: &str = "usage: intcode_debugger <day> [address=value ...]";
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+// usage: intcode_debugger <day> [address=value ...]
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let day: usize = args
+        .first()
+        .and_then(|day| day.parse().ok())
+        .unwrap_or_else(|| fail(USAGE));
+    let mut patch = Patch::new("command line");
+    for cell in args[1..].iter() {
+        match cell.split('=').collect::<Vec<&str>>().as_slice() {
+            [address, value] => match (address.parse(), value.parse()) {
+                (Ok(address), Ok(value)) => patch = patch.set(address, value),
+                _ => fail(&format!("not a number in {}", cell)),
+            },
+            _ => fail(&format!("patches look like address=value, got {}", cell)),
+        }
+    }
+    let data = Computer::load_data(day).unwrap_or_else(|error| fail(&error.to_string()));
+    let mut computer = Computer::new(&data, &[]);
+    if let Err(error) = computer.apply_patch(&patch) {
+        fail(&format!("can't apply {}: {}", patch, error));
+    }
+    let mut debugger = Debugger::new(computer);
+    let stdin = io::stdin();
+    repl(&mut debugger, stdin.lock(), &mut io::stdout()).unwrap();
+}
@@ -0,0 +1,17 @@
+use std::env;
+use std::io;
+
+use aoc_2019::intcode::ascii::AsciiComputer;
+use aoc_2019::intcode::play::{repl, Session};
+use aoc_2019::intcode::Computer;
+
+// usage: intcode_play [day], for the ascii programs, day 25 by default
+fn main() {
+    let day: usize = env::args()
+        .nth(1)
+        .map_or(25, |day| day.parse().expect("usage: intcode_play [day]"));
+    let program = Computer::load_data(day).unwrap();
+    let mut session = Session::new(AsciiComputer::new(&program));
+    let stdin = io::stdin();
+    repl(&mut session, stdin.lock(), &mut io::stdout()).unwrap();
+}
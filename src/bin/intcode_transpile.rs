@@ -0,0 +1,14 @@
+use std::env;
+
+use aoc_2019::intcode::transpile::transpile;
+use aoc_2019::intcode::Computer;
+
+// usage: intcode_transpile <day> <name> > src/intcode/compiled/day_<day>.rs
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (day, name) = match args.as_slice() {
+        [day, name] => (day.parse().expect("day should be a number"), name),
+        _ => panic!("usage: intcode_transpile <day> <name>"),
+    };
+    print!("{}", transpile(&Computer::load_data(day).unwrap(), name));
+}
@@ -1,4 +1,5 @@
-use super::intcode::Computer;
+use super::intcode::patch::Patch;
+use super::intcode::{Computer, State};
 
 #[derive(Default)]
 struct Game {
@@ -10,16 +11,12 @@ struct Game {
 
 impl Game {
     fn play(&mut self) -> i64 {
-        loop {
-            match (
-                self.computer.next(),
-                self.computer.next(),
-                self.computer.next(),
-            ) {
-                (Some(-1), Some(0), Some(score)) => self.score = score,
-                (Some(x), Some(y), Some(3)) => self.paddle = (x, y),
-                (Some(x), Some(y), Some(4)) => self.ball = (x, y),
-                (None, None, None) => break,
+        while let State::Output(x) = self.computer.run().unwrap() {
+            let y = self.computer.next().unwrap();
+            match (x, y, self.computer.next().unwrap()) {
+                (-1, 0, score) => self.score = score,
+                (x, y, 3) => self.paddle = (x, y),
+                (x, y, 4) => self.ball = (x, y),
                 (_, _, _) => (),
             }
             self.computer
@@ -30,7 +27,7 @@ impl Game {
 }
 
 pub fn solve_part_one() -> usize {
-    let computer = Computer::new(&Computer::load_data(13), &[]);
+    let computer = Computer::new(&Computer::load_data(13).unwrap(), &[]);
     computer
         .collect::<Vec<i64>>()
         .chunks_exact(3)
@@ -39,9 +36,10 @@ pub fn solve_part_one() -> usize {
 }
 
 pub fn solve_part_two() -> i64 {
-    let mut data = Computer::load_data(13);
-    data[0] = 2;
-    let computer = Computer::new(&data, &[]);
+    let mut computer = Computer::new(&Computer::load_data(13).unwrap(), &[]);
+    computer
+        .apply_patch(&Patch::new("free play").set(0, 2))
+        .unwrap();
     let mut game = Game {
         ball: (0, 0),
         paddle: (0, 0),
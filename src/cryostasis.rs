@@ -1,181 +1,289 @@
-#[cfg(test)]
-use itertools::Itertools;
+use std::collections::HashSet;
 
-use super::intcode::Computer;
-
-fn get_sand() -> String {
-    concat!("north,", "north,", "take sand,", "south,", "south,",).to_string()
-}
-
-fn get_space_heater() -> String {
-    concat!("south,", "take space heater,", "north,").to_string()
-}
+use itertools::Itertools;
 
-fn get_wreath() -> String {
-    concat!("south,", "west,", "take wreath,", "east,", "north,").to_string()
-}
+use super::intcode::ascii::{AsciiComputer, Reply};
+use super::intcode::{Computer, ComputerError};
 
-fn get_space_law_brochure() -> String {
-    concat!(
-        "south,",
-        "west,",
-        "south,",
-        "take space law space brochure,",
-        "north,",
-        "east,",
-        "north,"
-    )
-    .to_string()
-}
+// enough for any command, an item that sends the program into an infinite loop uses it up
+const TRAP_BUDGET: u64 = 1_000_000;
 
-fn get_pointer() -> String {
-    concat!(
-        "south,",
-        "west,",
-        "south,",
-        "south,",
-        "take pointer,",
-        "north,",
-        "north,",
-        "east,",
-        "north,"
-    )
-    .to_string()
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+struct Room {
+    name: String,
+    doors: Vec<String>,
+    items: Vec<String>,
 }
 
-fn get_planetoid() -> String {
-    concat!("west,", "south,", "take planetoid,", "north,", "east,").to_string()
+// the names of every room described in the text, in order
+fn room_names(text: &str) -> Vec<&str> {
+    text.lines()
+        .filter_map(|line| line.strip_prefix("== ")?.strip_suffix(" =="))
+        .collect()
 }
 
-fn get_loom() -> String {
-    concat!(
-        "south,",
-        "south,",
-        "east,",
-        "take loom,",
-        "west,",
-        "north,",
-        "north,"
-    )
-    .to_string()
+// the room the text ends up in
+fn parse_room(text: &str) -> Option<Room> {
+    let name = *room_names(text).last()?;
+    let description = &text[text.rfind(&format!("== {} ==", name))?..];
+    let list = |heading: &str| -> Vec<String> {
+        description
+            .lines()
+            .skip_while(|&line| line != heading)
+            .skip(1)
+            .map_while(|line| line.strip_prefix("- "))
+            .map(|line| line.to_string())
+            .collect()
+    };
+    Some(Room {
+        name: name.to_string(),
+        doors: list("Doors here lead:"),
+        items: list("Items here:"),
+    })
 }
 
-fn get_festive_hat() -> String {
-    concat!("west,", "west,", "take festive hat,", "east,", "east,").to_string()
+fn opposite(door: &str) -> &str {
+    match door {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        _ => "east",
+    }
 }
 
-fn bring_all_to_checkpoint() -> String {
-    vec![
-        get_sand(),
-        get_space_heater(),
-        get_wreath(),
-        get_space_law_brochure(),
-        get_pointer(),
-        get_planetoid(),
-        get_loom(),
-        get_festive_hat(),
-        concat!(
-            "west,",
-            "west,",
-            "south,",
-            "west,",
-            "drop sand,",
-            "drop space heater,",
-            "drop wreath,",
-            "drop space law space brochure,",
-            "drop planetoid,",
-            "drop loom,",
-            "drop festive hat,",
-        )
-        .to_string(),
-    ]
-    .join("")
+fn password(text: &str) -> Option<String> {
+    let password: String = text
+        .lines()
+        .skip_while(|&line| !line.contains("get in by typing"))
+        .flat_map(|line| line.chars())
+        .skip_while(|ch| !ch.is_ascii_digit())
+        .take_while(|ch| ch.is_ascii_digit())
+        .collect();
+    Some(password).filter(|password| !password.is_empty())
 }
 
-#[cfg(test)]
-fn take_items(items: &Vec<&str>) -> Vec<String> {
-    items.iter().map(|z| format!("take {}", z)).collect()
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Solution {
+    pub password: String,
+    // what the droid has to carry onto the pressure-sensitive floor
+    pub items: Vec<String>,
+    pub traps: Vec<String>,
 }
 
-#[cfg(test)]
-fn drop_items(items: &Vec<&str>) -> Vec<String> {
-    items.iter().map(|z| format!("drop {}", z)).collect()
+struct Explorer {
+    computer: AsciiComputer,
+    visited: HashSet<String>,
+    items: Vec<String>,
+    traps: Vec<String>,
+    // the way from the start to the security checkpoint, and the door to the floor from there
+    checkpoint: Option<(Vec<String>, String)>,
 }
 
-#[cfg(test)]
-fn find_item_combination(computer: &mut Computer) -> Vec<&str> {
-    let ascii = bring_all_to_checkpoint();
-    let input: Vec<&str> = ascii.split(",").filter(|z| !z.is_empty()).collect();
-    computer.add_input(&Computer::ascii_to_intcode(&input));
-    computer.last();
-    let all_items = vec![
-        "sand",
-        "space heater",
-        "wreath",
-        "space law space brochure",
-        "planetoid",
-        "loom",
-        "festive hat",
-    ];
-
-    for k in 1..all_items.len() {
-        for combination in all_items.clone().into_iter().combinations(k) {
-            computer.add_input(&Computer::ascii_to_intcode(
-                &take_items(&combination)
-                    .iter()
-                    .map(String::as_str)
-                    .collect(),
-            ));
-            computer.add_input(&Computer::ascii_to_intcode(&vec!["north"]));
-            if Computer::intcode_to_ascii(&computer.collect()).contains("get in by typing") {
-                return combination;
+impl Explorer {
+    fn new(computer: AsciiComputer) -> Self {
+        Explorer {
+            computer,
+            visited: HashSet::new(),
+            items: Vec::new(),
+            traps: Vec::new(),
+            checkpoint: None,
+        }
+    }
+    fn command(&mut self, line: &str) -> Result<Reply, ComputerError> {
+        self.computer.command(line)
+    }
+    // takes the item unless it turns out to be a trap, in which case the game is rolled back.
+    // running out of budget means the item sent the program into a loop, any other error is real
+    fn take(&mut self, item: &str, room: &Room) -> Result<(), ComputerError> {
+        let snapshot = self.computer.computer().save();
+        self.computer.computer_mut().set_budget(Some(TRAP_BUDGET));
+        let result = self.try_take(item, room);
+        self.computer.computer_mut().set_budget(None);
+        let safe = match result {
+            Err(ComputerError::BudgetExceeded { .. }) => false,
+            result => result?,
+        };
+        if safe {
+            self.items.push(item.to_string());
+        } else {
+            self.computer.computer_mut().restore(&snapshot);
+            self.traps.push(item.to_string());
+        }
+        Ok(())
+    }
+    // some items end the game or loop forever straight away, others only stop the droid moving
+    fn try_take(&mut self, item: &str, room: &Room) -> Result<bool, ComputerError> {
+        if self.command(&format!("take {}", item))?.halted() {
+            return Ok(false);
+        }
+        // with nowhere to go, only the take itself can give a trap away
+        let door = match room.doors.first() {
+            Some(door) => door,
+            None => return Ok(true),
+        };
+        let reply = self.command(door)?;
+        match room_names(&reply.text).len() {
+            0 => Ok(false),
+            // thrown back off the pressure-sensitive floor, so still in the same room
+            2 => Ok(!reply.halted()),
+            _ => Ok(!self.command(opposite(door))?.halted()),
+        }
+    }
+    fn explore(&mut self, room: Room, path: &mut Vec<String>) -> Result<(), ComputerError> {
+        self.visited.insert(room.name.clone());
+        for item in room.items.iter() {
+            self.take(item, &room)?;
+        }
+        for door in room.doors.iter() {
+            let snapshot = self.computer.computer().save();
+            let reply = self.command(door)?;
+            // the pressure-sensitive floor either throws the droid back or, if it happens to be
+            // carrying the right items already, ends the game. either way search comes back later
+            if reply.halted() || room_names(&reply.text).len() > 1 {
+                self.computer.computer_mut().restore(&snapshot);
+                self.checkpoint = Some((path.clone(), door.clone()));
+                continue;
+            }
+            let next = match parse_room(&reply.text) {
+                Some(next) => next,
+                None => continue,
+            };
+            if !self.visited.contains(&next.name) {
+                path.push(door.clone());
+                self.explore(next, path)?;
+                path.pop();
             }
-            computer.add_input(&Computer::ascii_to_intcode(
-                &drop_items(&combination)
-                    .iter()
-                    .map(String::as_str)
-                    .collect(),
-            ));
+            self.command(opposite(door))?;
         }
+        Ok(())
     }
-    Vec::new()
+    // tries every set of items on the floor, the smallest first
+    fn search(&mut self) -> Result<Option<Solution>, ComputerError> {
+        let (path, floor) = match self.checkpoint.clone() {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(None),
+        };
+        for door in path.iter() {
+            self.command(door)?;
+        }
+        let snapshot = self.computer.computer().save();
+        let items = self.items.clone();
+        for k in 0..=items.len() {
+            for combination in items.iter().combinations(k) {
+                self.computer.computer_mut().restore(&snapshot);
+                for item in items.iter().filter(|item| !combination.contains(item)) {
+                    self.command(&format!("drop {}", item))?;
+                }
+                if let Some(password) = password(&self.command(&floor)?.text) {
+                    return Ok(Some(Solution {
+                        password,
+                        items: combination.into_iter().cloned().collect(),
+                        traps: self.traps.clone(),
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+// explores the whole ship picking up everything that isn't a trap, then works out what to carry
+// through the security checkpoint
+pub fn solve(program: &[i64]) -> Result<Option<Solution>, ComputerError> {
+    let mut explorer = Explorer::new(AsciiComputer::new(program));
+    let start = match parse_room(&explorer.computer.read()?.text) {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    explorer.explore(start, &mut Vec::new())?;
+    explorer.search()
 }
 
 pub fn solve_part_one() -> String {
-    let ascii = bring_all_to_checkpoint();
-    let mut input: Vec<&str> = ascii.split(",").filter(|z| !z.is_empty()).collect();
-    input.append(&mut vec![
-        "take sand",
-        "take wreath",
-        "take planetoid",
-        "north",
-    ]);
-    let mut computer: Computer = Computer::new(
-        &Computer::load_data(25),
-        &Computer::ascii_to_intcode(&input),
-    );
-    computer.yield_on_empty = true;
-    let output = Computer::intcode_to_ascii(&computer.collect());
-    output
-        .lines()
-        .skip_while(|&line| !line.contains("get in by typing"))
-        .flat_map(|line| line.chars())
-        .skip_while(|ch| !ch.is_ascii_digit())
-        .take_while(|ch| ch.is_ascii_digit())
-        .collect()
+    solve(&Computer::load_data(25).unwrap())
+        .unwrap()
+        .unwrap()
+        .password
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::intcode::asm::assemble;
+
+    #[test]
+    fn test_parse_room() {
+        let text = concat!(
+            "\n\n\n== Pressure-Sensitive Floor ==\n",
+            "Analyzing...\n\n",
+            "Doors here lead:\n- south\n\n",
+            "A loud, robotic voice says \"Alert!\" and you are ejected back to the checkpoint.\n",
+            "\n\n\n== Security Checkpoint ==\n",
+            "In the next room, a pressure-sensitive floor will verify your identity.\n\n",
+            "Doors here lead:\n- north\n- east\n\n",
+            "Items here:\n- sand\n- space heater\n\n",
+            "Command?\n",
+        );
+        assert_eq!(
+            room_names(text),
+            vec!["Pressure-Sensitive Floor", "Security Checkpoint"]
+        );
+        assert_eq!(
+            parse_room(text),
+            Some(Room {
+                name: "Security Checkpoint".to_string(),
+                doors: vec!["north".to_string(), "east".to_string()],
+                items: vec!["sand".to_string(), "space heater".to_string()],
+            })
+        );
+        assert_eq!(parse_room("Command?\n"), None);
+    }
+
+    #[test]
+    fn test_take() {
+        let room = Room {
+            name: "Closet".to_string(),
+            doors: Vec::new(),
+            items: vec!["sand".to_string()],
+        };
+        let explorer = |text: &str| Explorer::new(AsciiComputer::new(&assemble(text).unwrap()));
+        // reads forever, and a room with no doors leaves nothing else to try
+        let mut reader = explorer("loop: in x\n jnz #1, #loop\n x: db 0");
+        assert_eq!(reader.take("sand", &room), Ok(()));
+        assert_eq!(reader.items, vec!["sand"]);
+        // a loop uses up the budget, which makes the item a trap
+        let mut spinner = explorer("in x\n spin: jnz #1, #spin\n x: db 0");
+        assert_eq!(spinner.take("sand", &room), Ok(()));
+        assert_eq!(spinner.traps, vec!["sand"]);
+        // but anything else is a real error
+        let mut broken = explorer("in x\n db 42\n x: db 0");
+        assert_eq!(
+            broken.take("sand", &room),
+            Err(ComputerError::UnknownOpCode {
+                opcode: 42,
+                address: 2
+            })
+        );
+    }
 
     #[test]
-    fn test_find_item_combination() {
-        let mut computer: Computer = Computer::new(&Computer::load_data(25), &[]);
-        computer.yield_on_empty = true;
+    fn test_solve() {
+        let solution = solve(&Computer::load_data(25).unwrap()).unwrap().unwrap();
+        let mut items = solution.items.clone();
+        items.sort();
+        // the old hand-written route never dropped the pointer, so it was always carried too
+        assert_eq!(items, vec!["planetoid", "pointer", "sand", "wreath"]);
+        let mut traps = solution.traps.clone();
+        traps.sort();
         assert_eq!(
-            find_item_combination(&mut computer),
-            vec!["sand", "wreath", "planetoid"]
+            traps,
+            vec![
+                "escape pod",
+                "giant electromagnet",
+                "infinite loop",
+                "molten lava",
+                "photons"
+            ]
         );
     }
 
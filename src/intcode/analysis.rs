@@ -0,0 +1,492 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+use super::disasm::{decode_at, is_jump, return_address, Line, Operand};
+use super::{OpCode, ParameterMode};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum EdgeKind {
+    Fallthrough,
+    Jump,
+    Call,
+    Return,
+}
+
+// edges run between block start addresses
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Edge {
+    pub from: i64,
+    pub to: i64,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Block {
+    pub start: i64,
+    pub end: i64,
+    pub lines: Vec<Line>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Analysis {
+    pub blocks: BTreeMap<i64, Block>,
+    pub edges: BTreeSet<Edge>,
+    pub inputs: Vec<i64>,
+    pub outputs: Vec<i64>,
+    // addresses of computed jumps whose targets could not be worked out
+    pub unresolved: Vec<i64>,
+    pub unreachable: Vec<Range<i64>>,
+    // instruction address and the code address it writes to. only position-mode writes are
+    // known here, transpiled code leaves writes through the relative base to Machine::pos,
+    // which checks the cells at run time
+    pub self_modifying: Vec<(i64, i64)>,
+}
+
+type Code = BTreeMap<usize, (OpCode, Vec<Operand>)>;
+
+// whether a jump can be taken and whether it can fall through, given a constant condition
+fn branches(opcode: OpCode, condition: &Operand) -> (bool, bool) {
+    if condition.mode != ParameterMode::Immediate {
+        return (true, true);
+    }
+    let taken = (condition.value != 0) == (opcode == OpCode::JumpIfTrue);
+    (taken, !taken)
+}
+
+fn end_of(address: usize, operands: &[Operand]) -> usize {
+    address + 1 + operands.len()
+}
+
+// the statically known target of a jump, either immediate or read from a cell nothing writes to
+fn static_target(jump: &[Operand], resolved: &BTreeMap<usize, i64>, address: usize) -> Option<i64> {
+    match jump[1].mode {
+        ParameterMode::Immediate => Some(jump[1].value),
+        ParameterMode::Position => resolved.get(&address).cloned(),
+        ParameterMode::Relative => None,
+    }
+}
+
+// follows every statically known path from address 0, including the return address of
+// anything that looks like a call
+fn traverse(program: &[i64], resolved: &BTreeMap<usize, i64>) -> (Code, Vec<bool>) {
+    let mut code = Code::new();
+    let mut claimed = vec![false; program.len()];
+    let mut pending = vec![0];
+    while let Some(address) = pending.pop() {
+        if address >= program.len() || claimed[address] {
+            continue;
+        }
+        let (opcode, operands) = match decode_at(program, address) {
+            Some(decoded) => decoded,
+            None => continue,
+        };
+        let end = end_of(address, &operands);
+        if claimed[address..end].iter().any(|&x| x) {
+            continue;
+        }
+        claimed[address..end].iter_mut().for_each(|x| *x = true);
+        match opcode {
+            OpCode::Stop => (),
+            OpCode::JumpIfTrue | OpCode::JumpIfFalse => {
+                let (taken, falls_through) = branches(opcode, &operands[0]);
+                if let Some(target) = static_target(&operands, resolved, address).filter(|_| taken)
+                {
+                    if (0..program.len() as i64).contains(&target) {
+                        pending.push(target as usize);
+                    }
+                }
+                if falls_through {
+                    pending.push(end);
+                }
+            }
+            _ => {
+                if let Some(ret) = return_address(program, end, opcode, &operands) {
+                    pending.push(ret);
+                }
+                pending.push(end);
+            }
+        }
+        code.insert(address, (opcode, operands));
+    }
+    (code, claimed)
+}
+
+// jumps through a position mode cell can be resolved when no reachable instruction writes to
+// that cell or to the jump itself. resolving them can reveal more code, so this repeats until nothing changes
+fn discover(program: &[i64]) -> (Code, Vec<bool>, BTreeMap<usize, i64>) {
+    let mut resolved = BTreeMap::new();
+    loop {
+        let (code, claimed) = traverse(program, &resolved);
+        let written: BTreeSet<i64> = code
+            .values()
+            .filter_map(|(opcode, operands)| {
+                let operand = operands[opcode.written_parameter()?];
+                Some(operand.value).filter(|_| operand.mode == ParameterMode::Position)
+            })
+            .collect();
+        let next: BTreeMap<usize, i64> = code
+            .iter()
+            .filter(|(_, (opcode, operands))| {
+                is_jump(*opcode) && operands[1].mode == ParameterMode::Position
+            })
+            .filter_map(|(&address, (_, operands))| {
+                // the jump's own operand being rewritten makes it just as dynamic
+                let cell = operands[1].value;
+                if written.contains(&cell)
+                    || written.contains(&(address as i64 + 2))
+                    || !(0..program.len() as i64).contains(&cell)
+                {
+                    return None;
+                }
+                Some((address, program[cell as usize]))
+            })
+            .collect();
+        if next == resolved {
+            return (code, claimed, resolved);
+        }
+        resolved = next;
+    }
+}
+
+pub fn analyze(program: &[i64]) -> Analysis {
+    let (code, claimed, resolved) = discover(program);
+
+    // call sites by the address of the jump, with the function and the return address.
+    // calls through a function pointer that can't be resolved are kept apart so they are not
+    // mistaken for returns
+    let mut calls: BTreeMap<usize, (i64, usize)> = BTreeMap::new();
+    let mut indirect_calls: BTreeSet<usize> = BTreeSet::new();
+    for (&address, (opcode, operands)) in code.iter() {
+        let end = end_of(address, operands);
+        if let Some(ret) = return_address(program, end, *opcode, operands) {
+            if let Some((_, jump)) = code.get(&end) {
+                match static_target(jump, &resolved, end) {
+                    Some(function) => {
+                        calls.insert(end, (function, ret));
+                    }
+                    None => {
+                        indirect_calls.insert(end);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut targets: BTreeMap<usize, Vec<(i64, EdgeKind)>> = BTreeMap::new();
+    let mut leaders: BTreeSet<usize> = vec![0].into_iter().collect();
+    for (&address, (opcode, operands)) in code.iter() {
+        if !is_jump(*opcode) {
+            continue;
+        }
+        leaders.insert(end_of(address, operands));
+        let (taken, _) = branches(*opcode, &operands[0]);
+        if let Some(target) = static_target(operands, &resolved, address).filter(|_| taken) {
+            let kind = if calls.contains_key(&address) {
+                EdgeKind::Call
+            } else {
+                EdgeKind::Jump
+            };
+            targets.entry(address).or_default().push((target, kind));
+            if target >= 0 {
+                leaders.insert(target as usize);
+            }
+        }
+    }
+
+    // split the reachable code into blocks
+    let mut blocks: BTreeMap<i64, Block> = BTreeMap::new();
+    let mut current: Option<Block> = None;
+    for (&address, (opcode, operands)) in code.iter() {
+        let starts_block = match current.as_ref() {
+            Some(block) => leaders.contains(&address) || block.end != address as i64,
+            None => true,
+        };
+        if starts_block {
+            if let Some(block) = current.take() {
+                blocks.insert(block.start, block);
+            }
+            current = Some(Block {
+                start: address as i64,
+                end: address as i64,
+                lines: Vec::new(),
+            });
+        }
+        let block = current.as_mut().unwrap();
+        block.end = end_of(address, operands) as i64;
+        block.lines.push(Line::Code {
+            address: address as i64,
+            opcode: *opcode,
+            operands: operands.clone(),
+        });
+        if is_jump(*opcode) || *opcode == OpCode::Stop {
+            blocks.insert(block.start, current.take().unwrap());
+        }
+    }
+    if let Some(block) = current.take() {
+        blocks.insert(block.start, block);
+    }
+
+    let mut edges = BTreeSet::new();
+    let mut returns: BTreeMap<i64, usize> = BTreeMap::new();
+    for block in blocks.values() {
+        let (address, opcode, operands) = match block.lines.last() {
+            Some(Line::Code {
+                address,
+                opcode,
+                operands,
+            }) => (*address as usize, *opcode, operands),
+            _ => continue,
+        };
+        let falls_through = match opcode {
+            OpCode::Stop => false,
+            OpCode::JumpIfTrue | OpCode::JumpIfFalse => {
+                for &(to, kind) in targets.get(&address).into_iter().flatten() {
+                    if blocks.contains_key(&to) {
+                        edges.insert(Edge {
+                            from: block.start,
+                            to,
+                            kind,
+                        });
+                    }
+                }
+                if operands[1].mode == ParameterMode::Relative && !indirect_calls.contains(&address)
+                {
+                    returns.insert(block.start, address);
+                }
+                branches(opcode, &operands[0]).1
+            }
+            _ => true,
+        };
+        if falls_through && blocks.contains_key(&block.end) {
+            edges.insert(Edge {
+                from: block.start,
+                to: block.end,
+                kind: EdgeKind::Fallthrough,
+            });
+        }
+    }
+
+    // a jump through the relative base inside a function returns to wherever it was called
+    // from. the function body is everything reachable from its entry without entering calls
+    let mut resolved_returns = BTreeSet::new();
+    let functions: BTreeSet<i64> = calls.values().map(|&(function, _)| function).collect();
+    for &function in functions.iter() {
+        let mut seen = BTreeSet::new();
+        let mut pending = vec![function];
+        while let Some(start) = pending.pop() {
+            if !blocks.contains_key(&start) || !seen.insert(start) {
+                continue;
+            }
+            for edge in edges.iter().filter(|edge| edge.from == start) {
+                match edge.kind {
+                    EdgeKind::Call => {
+                        let jump = blocks[&start].lines.last().unwrap().address() as usize;
+                        pending.push(calls[&jump].1 as i64);
+                    }
+                    _ => pending.push(edge.to),
+                }
+            }
+        }
+        for (&start, &jump) in returns.iter().filter(|(start, _)| seen.contains(start)) {
+            resolved_returns.insert(jump);
+            for &(_, ret) in calls.values().filter(|&&(f, _)| f == function) {
+                if blocks.contains_key(&(ret as i64)) {
+                    edges.insert(Edge {
+                        from: start,
+                        to: ret as i64,
+                        kind: EdgeKind::Return,
+                    });
+                }
+            }
+        }
+    }
+
+    let unresolved = code
+        .iter()
+        .filter(|(address, (opcode, operands))| {
+            is_jump(*opcode)
+                && branches(*opcode, &operands[0]).0
+                && operands[1].mode != ParameterMode::Immediate
+                && !resolved.contains_key(address)
+                && !resolved_returns.contains(address)
+        })
+        .map(|(&address, _)| address as i64)
+        .collect();
+    let sites = |wanted: OpCode| {
+        code.iter()
+            .filter(|(_, (opcode, _))| *opcode == wanted)
+            .map(|(&address, _)| address as i64)
+            .collect()
+    };
+    let self_modifying = code
+        .iter()
+        .filter_map(|(&address, (opcode, operands))| {
+            let operand = operands[opcode.written_parameter()?];
+            let target = operand.value;
+            let hits_code = operand.mode == ParameterMode::Position
+                && (0..program.len() as i64).contains(&target)
+                && claimed[target as usize];
+            Some((address as i64, target)).filter(|_| hits_code)
+        })
+        .collect();
+    let mut unreachable = Vec::new();
+    let mut address = 0;
+    while address < program.len() {
+        let end = (address..program.len())
+            .find(|&i| claimed[i] != claimed[address])
+            .unwrap_or(program.len());
+        if !claimed[address] {
+            unreachable.push(address as i64..end as i64);
+        }
+        address = end;
+    }
+
+    Analysis {
+        blocks,
+        edges,
+        inputs: sites(OpCode::Input),
+        outputs: sites(OpCode::Output),
+        unresolved,
+        unreachable,
+        self_modifying,
+    }
+}
+
+impl Analysis {
+    // graphviz source for the control flow graph. blocks doing io are filled in and blocks
+    // ending in an unresolved jump are outlined in red
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph cfg {\n    node [shape=box, fontname=monospace];\n");
+        for block in self.blocks.values() {
+            let text: String = block
+                .lines
+                .iter()
+                .map(|line| format!("{}: {}\\l", line.address(), line.text()))
+                .collect();
+            let contains = |sites: &[i64]| {
+                sites
+                    .iter()
+                    .any(|site| (block.start..block.end).contains(site))
+            };
+            let mut attributes = vec![format!("label=\"{}\"", text)];
+            if contains(&self.inputs) || contains(&self.outputs) {
+                attributes.push("style=filled, fillcolor=lightblue".to_string());
+            }
+            if contains(&self.unresolved) {
+                attributes.push("color=red".to_string());
+            }
+            dot += &format!("    b{} [{}];\n", block.start, attributes.join(", "));
+        }
+        for edge in self.edges.iter() {
+            let attributes = match edge.kind {
+                EdgeKind::Fallthrough => "",
+                EdgeKind::Jump => " [color=blue]",
+                EdgeKind::Call => " [label=call]",
+                EdgeKind::Return => " [label=ret, style=dashed]",
+            };
+            dot += &format!("    b{} -> b{}{};\n", edge.from, edge.to, attributes);
+        }
+        dot + "}\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+    use crate::intcode::Computer;
+
+    fn program() -> Vec<i64> {
+        assemble(
+            "
+                in x
+                jz x, #skip
+                add #0, #back, rb+0
+                jnz #1, #double
+            back:
+                out x
+            skip:
+                add #7, #0, patch
+                jz #0, vector
+            patch:
+                hlt
+            double:
+                mul x, #2, x
+                jz #0, rb+0
+                db 1, 2, 3
+            vector: db patch
+            x:      db 0
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_analyze() {
+        let analysis = analyze(&program());
+        let starts: Vec<i64> = analysis.blocks.keys().cloned().collect();
+        assert_eq!(starts, vec![0, 5, 12, 14, 21, 22]);
+        let edge = |from, to, kind| Edge { from, to, kind };
+        assert_eq!(
+            analysis.edges,
+            vec![
+                edge(0, 5, EdgeKind::Fallthrough),
+                edge(0, 14, EdgeKind::Jump),
+                edge(5, 22, EdgeKind::Call),
+                edge(12, 14, EdgeKind::Fallthrough),
+                edge(14, 21, EdgeKind::Jump),
+                edge(22, 12, EdgeKind::Return),
+            ]
+            .into_iter()
+            .collect()
+        );
+        assert_eq!(analysis.inputs, vec![0]);
+        assert_eq!(analysis.outputs, vec![12]);
+        assert_eq!(analysis.unresolved, Vec::<i64>::new());
+        assert_eq!(analysis.unreachable, vec![29..34]);
+        assert_eq!(analysis.self_modifying, vec![(14, 21)]);
+    }
+
+    #[test]
+    fn test_unresolved_jumps() {
+        // the jump target is read from a cell the program also writes to
+        let program = assemble("in vector\njnz #1, vector\nhlt\nvector: db 5").unwrap();
+        let analysis = analyze(&program);
+        assert_eq!(analysis.unresolved, vec![2]);
+        assert_eq!(analysis.unreachable, vec![5..7]);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = analyze(&program()).to_dot();
+        assert!(dot.starts_with("digraph cfg {\n"));
+        assert!(dot.contains(
+            "    b0 [label=\"0: in 33\\l2: jz 33, #14\\l\", style=filled, fillcolor=lightblue];\n"
+        ));
+        assert!(dot.contains("    b5 -> b22 [label=call];\n"));
+        assert!(dot.contains("    b22 -> b12 [label=ret, style=dashed];\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_analyze_puzzle_programs() {
+        // day 21 has one indirect dispatch jump, through a function pointer cell. day 25 has
+        // one that patches the target into the jump instruction itself (2048), and three calls
+        // through function pointers passed on the stack
+        let values: Vec<(usize, Vec<i64>)> =
+            vec![(21, vec![2034]), (25, vec![1159, 1219, 1542, 2048])];
+        for (day, unresolved) in values {
+            let analysis = analyze(&Computer::load_data(day).unwrap());
+            assert_eq!(analysis.unresolved, unresolved, "day {}", day);
+            assert!(!analysis.inputs.is_empty());
+            // every block is reached by some edge, apart from the entry and the places
+            // unresolved calls return to
+            let reached: BTreeSet<i64> = analysis.edges.iter().map(|edge| edge.to).collect();
+            assert!(analysis.blocks.keys().all(|start| *start == 0
+                || reached.contains(start)
+                || unresolved.contains(&(start - 3))));
+        }
+        let analysis = analyze(&Computer::load_data(25).unwrap());
+        assert!(analysis.self_modifying.contains(&(2040, 2050)));
+    }
+}
@@ -0,0 +1,221 @@
+use std::fmt;
+
+use super::{Computer, ComputerError, State};
+
+// every line followed by a newline, the way ascii programs expect their input
+pub fn encode(lines: &[&str]) -> Vec<i64> {
+    lines
+        .iter()
+        .flat_map(|line| line.chars().chain(Some('\n')))
+        .map(|ch| ch as i64)
+        .collect()
+}
+
+fn is_ascii(value: i64) -> bool {
+    (0..128).contains(&value)
+}
+
+// splits output into its text and the values that aren't ascii, like a final answer
+pub fn decode(output: &[i64]) -> (String, Vec<i64>) {
+    let text = output
+        .iter()
+        .filter(|&&value| is_ascii(value))
+        .map(|&value| value as u8 as char)
+        .collect();
+    let values = output
+        .iter()
+        .cloned()
+        .filter(|&value| !is_ascii(value))
+        .collect();
+    (text, values)
+}
+
+// everything printed between two inputs
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Reply {
+    pub text: String,
+    pub values: Vec<i64>,
+    // NeedsInput or Halted, or Output if reading stopped at a prompt
+    pub state: State,
+}
+
+impl Reply {
+    pub fn halted(&self) -> bool {
+        self.state == State::Halted
+    }
+    // the last value that wasn't ascii, which is usually the answer
+    pub fn value(&self) -> Option<i64> {
+        self.values.last().cloned()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Entry {
+    Sent(String),
+    Received(String),
+    Value(i64),
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Entry::Sent(line) => writeln!(f, "> {}", line),
+            Entry::Received(text) => write!(f, "{}", text),
+            Entry::Value(value) => writeln!(f, "[{}]", value),
+        }
+    }
+}
+
+// talks to a program that reads and prints lines of text, keeping a transcript of both sides
+#[derive(Debug)]
+pub struct AsciiComputer {
+    computer: Computer,
+    transcript: Vec<Entry>,
+}
+
+impl AsciiComputer {
+    pub fn new(program: &[i64]) -> Self {
+        AsciiComputer::from_computer(Computer::with_memory(program, &[]))
+    }
+    // for a computer that needs patching or restoring first
+    pub fn from_computer(mut computer: Computer) -> Self {
+        computer.yield_on_empty = true;
+        AsciiComputer {
+            computer,
+            transcript: Vec::new(),
+        }
+    }
+    pub fn computer(&self) -> &Computer {
+        &self.computer
+    }
+    pub fn computer_mut(&mut self) -> &mut Computer {
+        &mut self.computer
+    }
+    pub fn transcript(&self) -> &[Entry] {
+        &self.transcript
+    }
+    // the whole conversation as it would look on a terminal
+    pub fn transcript_text(&self) -> String {
+        self.transcript
+            .iter()
+            .map(|entry| entry.to_string())
+            .collect()
+    }
+    pub fn send(&mut self, line: &str) {
+        self.computer.add_input(&encode(&[line]));
+        self.transcript.push(Entry::Sent(line.to_string()));
+    }
+    pub fn send_lines(&mut self, lines: &[&str]) {
+        lines.iter().for_each(|line| self.send(line));
+    }
+    // runs until the program wants input that hasn't been sent or halts
+    pub fn read(&mut self) -> Result<Reply, ComputerError> {
+        self.read_while(|_| true)
+    }
+    // like read, but also stops as soon as the text ends with the prompt
+    pub fn read_until(&mut self, prompt: &str) -> Result<Reply, ComputerError> {
+        self.read_while(|text| !text.ends_with(prompt))
+    }
+    pub fn command(&mut self, line: &str) -> Result<Reply, ComputerError> {
+        self.send(line);
+        self.read()
+    }
+    fn read_while<F: Fn(&str) -> bool>(&mut self, more: F) -> Result<Reply, ComputerError> {
+        let mut reply = Reply {
+            text: String::new(),
+            values: Vec::new(),
+            state: State::NeedsInput,
+        };
+        let result = loop {
+            match self.computer.run() {
+                Ok(State::Output(value)) if is_ascii(value) => {
+                    reply.text.push(value as u8 as char);
+                    if !more(&reply.text) {
+                        reply.state = State::Output(value);
+                        break Ok(());
+                    }
+                }
+                Ok(State::Output(value)) => reply.values.push(value),
+                Ok(state) => {
+                    reply.state = state;
+                    break Ok(());
+                }
+                Err(error) => break Err(error),
+            }
+        };
+        // whatever was printed before an error still goes in the transcript
+        if !reply.text.is_empty() {
+            self.transcript.push(Entry::Received(reply.text.clone()));
+        }
+        self.transcript
+            .extend(reply.values.iter().map(|&value| Entry::Value(value)));
+        result.map(|_| reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    #[test]
+    fn test_encode_decode() {
+        assert_eq!(encode(&["A,B", "n"]), vec![65, 44, 66, 10, 110, 10]);
+        assert_eq!(
+            decode(&[104, 105, 10, 1000, -1]),
+            ("hi\n".to_string(), vec![1000, -1])
+        );
+    }
+
+    #[test]
+    fn test_conversation() {
+        // asks for a character, then prints it back followed by its code plus 1000
+        let program = assemble(
+            "
+                out #63
+                out #10
+                in x
+                in nl
+                out x
+                out nl
+                add x, #1000, x
+                out x
+                hlt
+            x:  db 0
+            nl: db 0
+            ",
+        )
+        .unwrap();
+        let mut computer = AsciiComputer::new(&program);
+        let prompt = computer.read().unwrap();
+        assert_eq!(
+            (prompt.text.as_str(), prompt.state),
+            ("?\n", State::NeedsInput)
+        );
+        let reply = computer.command("a").unwrap();
+        assert_eq!(reply.text, "a\n");
+        assert_eq!(reply.value(), Some(1097));
+        assert!(reply.halted());
+        assert_eq!(computer.transcript_text(), "?\n> a\na\n[1097]\n");
+    }
+
+    #[test]
+    fn test_read_until() {
+        let program = assemble(
+            "
+                out #62
+                out #32
+                out #33
+                hlt
+            ",
+        )
+        .unwrap();
+        let mut computer = AsciiComputer::new(&program);
+        let reply = computer.read_until("> ").unwrap();
+        assert_eq!(
+            (reply.text.as_str(), reply.state),
+            ("> ", State::Output(32))
+        );
+        assert_eq!(computer.read().unwrap().text, "!");
+    }
+}
@@ -0,0 +1,535 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use super::{Instruction, OpCode, ParameterMode};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AsmError {
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    UnknownMnemonic {
+        line: usize,
+        mnemonic: String,
+    },
+    OperandCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    ImmediateWrite {
+        line: usize,
+    },
+    UndefinedLabel {
+        line: usize,
+        label: String,
+    },
+    DuplicateLabel {
+        line: usize,
+        label: String,
+    },
+    Overflow {
+        line: usize,
+    },
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsmError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            AsmError::UnknownMnemonic { line, mnemonic } => {
+                write!(f, "line {}: unknown mnemonic {:?}", line, mnemonic)
+            }
+            AsmError::OperandCount {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} operands, found {}",
+                line, expected, found
+            ),
+            AsmError::ImmediateWrite { line } => {
+                write!(f, "line {}: cannot write to an immediate operand", line)
+            }
+            AsmError::UndefinedLabel { line, label } => {
+                write!(f, "line {}: undefined label {:?}", line, label)
+            }
+            AsmError::DuplicateLabel { line, label } => {
+                write!(f, "line {}: label {:?} is already defined", line, label)
+            }
+            AsmError::Overflow { line } => write!(f, "line {}: expression overflows", line),
+        }
+    }
+}
+
+impl Error for AsmError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    // unsigned so that the magnitude of i64::MIN can follow a minus sign
+    Number(u64),
+    Ident(String),
+    Text(String),
+    Symbol(char),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(i64),
+    Label(String),
+    Here,
+    Negate(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Subtract(Box<Expr>, Box<Expr>),
+    Multiply(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, labels: &HashMap<String, i64>, here: i64, line: usize) -> Result<i64, AsmError> {
+        let eval = |expr: &Expr| expr.eval(labels, here, line);
+        let result = match self {
+            Expr::Number(x) => Some(*x),
+            Expr::Label(label) => {
+                Some(*labels.get(label).ok_or_else(|| AsmError::UndefinedLabel {
+                    line,
+                    label: label.clone(),
+                })?)
+            }
+            Expr::Here => Some(here),
+            Expr::Negate(a) => eval(a)?.checked_neg(),
+            Expr::Add(a, b) => eval(a)?.checked_add(eval(b)?),
+            Expr::Subtract(a, b) => eval(a)?.checked_sub(eval(b)?),
+            Expr::Multiply(a, b) => eval(a)?.checked_mul(eval(b)?),
+        };
+        result.ok_or(AsmError::Overflow { line })
+    }
+}
+
+#[derive(Debug)]
+enum Statement {
+    Code {
+        opcode: OpCode,
+        operands: Vec<(ParameterMode, Expr)>,
+    },
+    Data(Vec<Expr>),
+}
+
+impl Statement {
+    fn len(&self) -> usize {
+        match self {
+            Statement::Code { operands, .. } => 1 + operands.len(),
+            Statement::Data(values) => values.len(),
+        }
+    }
+}
+
+fn tokenize(text: &str, line: usize) -> Result<Vec<Token>, AsmError> {
+    let syntax = |message: String| AsmError::Syntax { line, message };
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+        } else if ch.is_ascii_digit() {
+            let mut digits = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                digits.push(d);
+                chars.next();
+            }
+            let value = digits
+                .parse()
+                .map_err(|_| syntax(format!("number {} is too large", digits)))?;
+            tokens.push(Token::Number(value));
+        } else if ch.is_alphabetic() || ch == '_' {
+            let mut ident = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Ident(ident));
+        } else if ch == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some('n') => text.push('\n'),
+                        Some('t') => text.push('\t'),
+                        Some(c @ '\\') | Some(c @ '"') => text.push(c),
+                        c => return Err(syntax(format!("bad escape sequence {:?}", c))),
+                    },
+                    Some(c) => text.push(c),
+                    None => return Err(syntax("unterminated string".to_string())),
+                }
+            }
+            tokens.push(Token::Text(text));
+        } else if "#+-*(),$".contains(ch) {
+            tokens.push(Token::Symbol(ch));
+            chars.next();
+        } else {
+            return Err(syntax(format!("unexpected character {:?}", ch)));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+    fn error<T>(&self, message: &str) -> Result<T, AsmError> {
+        Err(AsmError::Syntax {
+            line: self.line,
+            message: format!("{}, found {:?}", message, self.peek()),
+        })
+    }
+    fn expr(&mut self) -> Result<Expr, AsmError> {
+        let first = self.term()?;
+        self.sum(first)
+    }
+    // continues a sum whose first term has already been parsed
+    fn sum(&mut self, mut result: Expr) -> Result<Expr, AsmError> {
+        loop {
+            if self.eat('+') {
+                result = Expr::Add(Box::new(result), Box::new(self.term()?));
+            } else if self.eat('-') {
+                result = Expr::Subtract(Box::new(result), Box::new(self.term()?));
+            } else {
+                return Ok(result);
+            }
+        }
+    }
+    fn term(&mut self) -> Result<Expr, AsmError> {
+        let mut result = self.factor()?;
+        while self.eat('*') {
+            result = Expr::Multiply(Box::new(result), Box::new(self.factor()?));
+        }
+        Ok(result)
+    }
+    fn factor(&mut self) -> Result<Expr, AsmError> {
+        if self.eat('-') {
+            // a negative literal is read whole, which is the only way to write i64::MIN
+            if let Some(&Token::Number(x)) = self.peek() {
+                if x <= i64::MAX as u64 + 1 {
+                    self.pos += 1;
+                    return Ok(Expr::Number((x as i64).wrapping_neg()));
+                }
+            }
+            return Ok(Expr::Negate(Box::new(self.factor()?)));
+        }
+        if self.eat('$') {
+            return Ok(Expr::Here);
+        }
+        if self.eat('(') {
+            let result = self.expr()?;
+            if !self.eat(')') {
+                return self.error("expected ')'");
+            }
+            return Ok(result);
+        }
+        match self.peek().cloned() {
+            Some(Token::Number(x)) if x <= i64::MAX as u64 => {
+                self.pos += 1;
+                Ok(Expr::Number(x as i64))
+            }
+            Some(Token::Number(x)) => Err(AsmError::Syntax {
+                line: self.line,
+                message: format!("number {} is too large", x),
+            }),
+            Some(Token::Ident(label)) => {
+                self.pos += 1;
+                Ok(Expr::Label(label))
+            }
+            _ => self.error("expected an expression"),
+        }
+    }
+    fn operand(&mut self) -> Result<(ParameterMode, Expr), AsmError> {
+        if self.eat('#') {
+            return Ok((ParameterMode::Immediate, self.expr()?));
+        }
+        if self.peek() == Some(&Token::Ident("rb".to_string())) {
+            self.pos += 1;
+            return Ok((ParameterMode::Relative, self.sum(Expr::Number(0))?));
+        }
+        Ok((ParameterMode::Position, self.expr()?))
+    }
+    fn separated<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Parser) -> Result<T, AsmError>,
+    ) -> Result<Vec<T>, AsmError> {
+        let mut result = Vec::new();
+        if self.peek().is_none() {
+            return Ok(result);
+        }
+        loop {
+            result.push(item(self)?);
+            if self.peek().is_none() {
+                return Ok(result);
+            }
+            if !self.eat(',') {
+                return self.error("expected ','");
+            }
+        }
+    }
+    fn data(&mut self) -> Result<Vec<Expr>, AsmError> {
+        let values = self.separated(|parser| match parser.peek().cloned() {
+            Some(Token::Text(text)) => {
+                parser.pos += 1;
+                Ok(text.chars().map(|ch| Expr::Number(ch as i64)).collect())
+            }
+            _ => Ok(vec![parser.expr()?]),
+        })?;
+        Ok(values.into_iter().flatten().collect())
+    }
+}
+
+fn strip_comment(text: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, ch) in text.char_indices() {
+        match ch {
+            ';' if !in_string => return &text[..i],
+            '"' if !escaped => in_string = !in_string,
+            _ => (),
+        }
+        escaped = ch == '\\' && !escaped;
+    }
+    text
+}
+
+fn parse_line(
+    text: &str,
+    line: usize,
+    address: i64,
+    labels: &mut HashMap<String, i64>,
+) -> Result<Option<Statement>, AsmError> {
+    let mut text = strip_comment(text).trim();
+    // leading `name:` definitions, a colon further on can be inside a string
+    loop {
+        let end = text
+            .find(|ch: char| ch.is_whitespace() || ch == '"' || ch == ':')
+            .unwrap_or(text.len());
+        let name = &text[..end];
+        let rest = match text[end..].trim_start().strip_prefix(':') {
+            Some(rest) => rest,
+            None => break,
+        };
+        let is_ident = name.starts_with(|ch: char| ch.is_alphabetic() || ch == '_')
+            && name.chars().all(|ch| ch.is_alphanumeric() || ch == '_');
+        if !is_ident || name == "rb" {
+            return Err(AsmError::Syntax {
+                line,
+                message: format!("invalid label {:?}", name),
+            });
+        }
+        if labels.insert(name.to_string(), address).is_some() {
+            return Err(AsmError::DuplicateLabel {
+                line,
+                label: name.to_string(),
+            });
+        }
+        text = rest.trim();
+    }
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let (mnemonic, rest) = match text.find(char::is_whitespace) {
+        Some(i) => (&text[..i], &text[i..]),
+        None => (text, ""),
+    };
+    let mut parser = Parser {
+        tokens: tokenize(rest, line)?,
+        pos: 0,
+        line,
+    };
+    if mnemonic == "db" {
+        return Ok(Some(Statement::Data(parser.data()?)));
+    }
+    let opcode = OpCode::from_mnemonic(mnemonic).ok_or_else(|| AsmError::UnknownMnemonic {
+        line,
+        mnemonic: mnemonic.to_string(),
+    })?;
+    let operands = parser.separated(Parser::operand)?;
+    if operands.len() != opcode.parameter_count() {
+        return Err(AsmError::OperandCount {
+            line,
+            expected: opcode.parameter_count(),
+            found: operands.len(),
+        });
+    }
+    if let Some(i) = opcode.written_parameter() {
+        if operands[i].0 == ParameterMode::Immediate {
+            return Err(AsmError::ImmediateWrite { line });
+        }
+    }
+    Ok(Some(Statement::Code { opcode, operands }))
+}
+
+pub fn assemble(text: &str) -> Result<Vec<i64>, AsmError> {
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+    for (i, line) in text.lines().enumerate() {
+        if let Some(statement) = parse_line(line, i + 1, address, &mut labels)? {
+            let len = statement.len() as i64;
+            statements.push((i + 1, address, statement));
+            address += len;
+        }
+    }
+
+    let mut program = Vec::with_capacity(address as usize);
+    for (line, address, statement) in statements {
+        match statement {
+            Statement::Code { opcode, operands } => {
+                let modes: Vec<ParameterMode> = operands.iter().map(|(mode, _)| *mode).collect();
+                program.push(Instruction::new(opcode, &modes).encode());
+                for (_, expr) in operands.iter() {
+                    program.push(expr.eval(&labels, address, line)?);
+                }
+            }
+            Statement::Data(values) => {
+                for expr in values.iter() {
+                    program.push(expr.eval(&labels, address, line)?);
+                }
+            }
+        }
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::disasm::disassemble;
+    use crate::intcode::Computer;
+
+    #[test]
+    fn test_assemble() {
+        let program = assemble(
+            "
+            ; doubles its input until it reaches 100
+            start:
+                in value
+            loop: mul value, #2, value
+                lt value, #100, flag
+                jnz flag, #loop
+                out rb + 2 * 3 - 1
+                jz #0, #$ + 3
+                hlt
+            value: db 0
+            flag:  db 0, \"a;\\\"\", end - start
+            end:
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            program,
+            vec![
+                3, 19, 1002, 19, 2, 19, 1007, 19, 100, 20, 1005, 20, 2, 204, 5, 1106, 0, 18, 99, 0,
+                0, 97, 59, 34, 25
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let values: Vec<(&str, AsmError)> = vec![
+            (
+                "foo 1",
+                AsmError::UnknownMnemonic {
+                    line: 1,
+                    mnemonic: "foo".to_string(),
+                },
+            ),
+            (
+                "\nadd 1, 2",
+                AsmError::OperandCount {
+                    line: 2,
+                    expected: 3,
+                    found: 2,
+                },
+            ),
+            ("in #4", AsmError::ImmediateWrite { line: 1 }),
+            (
+                "jnz #1, #nowhere",
+                AsmError::UndefinedLabel {
+                    line: 1,
+                    label: "nowhere".to_string(),
+                },
+            ),
+            (
+                "a: hlt\na: hlt",
+                AsmError::DuplicateLabel {
+                    line: 2,
+                    label: "a".to_string(),
+                },
+            ),
+        ];
+        for (text, error) in values {
+            assert_eq!(assemble(text), Err(error));
+        }
+        // constant expressions are checked, and i64::MIN can be written as a literal
+        assert_eq!(
+            assemble("db 9223372036854775807 + 1"),
+            Err(AsmError::Overflow { line: 1 })
+        );
+        assert_eq!(
+            assemble("db -(-9223372036854775808)"),
+            Err(AsmError::Overflow { line: 1 })
+        );
+        assert_eq!(
+            assemble("db -9223372036854775808, 9223372036854775807"),
+            Ok(vec![i64::MIN, i64::MAX])
+        );
+        assert_eq!(
+            assemble("db 9223372036854775808"),
+            Err(AsmError::Syntax {
+                line: 1,
+                message: "number 9223372036854775808 is too large".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("rb: hlt"),
+            Err(AsmError::Syntax {
+                line: 1,
+                message: "invalid label \"rb\"".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_colon_in_string() {
+        let program = assemble("prompt: db \"Command: go\" ; a: comment\nhlt").unwrap();
+        let mut expected: Vec<i64> = "Command: go".chars().map(|ch| ch as i64).collect();
+        expected.push(99);
+        assert_eq!(program, expected);
+        assert_eq!(assemble("db \"Command:\"\nhlt").unwrap().len(), 9);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for &day in [2, 5, 7, 9, 11, 13, 15, 17, 19, 21, 23, 25].iter() {
+            let program = Computer::load_data(day).unwrap();
+            let text = disassemble(&program).to_string();
+            assert_eq!(assemble(&text), Ok(program), "day {}", day);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use super::Instruction;
+
+// addresses past this are decoded every time rather than growing the cache
+const MAX_CACHED_ADDRESS: i64 = 1 << 16;
+
+// decoded instructions by address. entries remember the raw value they were decoded from, so
+// an edit made directly through Computer::program is noticed even without an invalidate
+#[derive(Debug, Clone)]
+pub(super) struct DecodeCache {
+    pub(super) enabled: bool,
+    entries: Vec<Option<(i64, Instruction)>>,
+}
+
+impl Default for DecodeCache {
+    fn default() -> Self {
+        DecodeCache {
+            enabled: true,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl DecodeCache {
+    pub(super) fn get(&self, address: i64, value: i64) -> Option<Instruction> {
+        match self.entries.get(address as usize) {
+            Some(&Some((raw, instruction))) if raw == value => Some(instruction),
+            _ => None,
+        }
+    }
+    pub(super) fn insert(&mut self, address: i64, value: i64, instruction: Instruction) {
+        if !self.enabled || address >= MAX_CACHED_ADDRESS {
+            return;
+        }
+        let index = address as usize;
+        if index >= self.entries.len() {
+            self.entries.resize(index + 1, None);
+        }
+        self.entries[index] = Some((value, instruction));
+    }
+    pub(super) fn invalidate(&mut self, address: i64) {
+        if let Some(entry) = self.entries.get_mut(address as usize) {
+            *entry = None;
+        }
+    }
+    pub(super) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::network::Packet;
+use super::snapshot::{join, parse_error, parse_list, parse_number};
+use super::{Computer, ComputerError, Memory, State};
+
+const HEADER: &str = "intcode-capture 1";
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Record {
+    // the scheduling round the packet was sent in
+    pub tick: usize,
+    pub packet: Packet,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Capture {
+    pub arity: usize,
+    pub records: Vec<Record>,
+}
+
+impl Capture {
+    pub fn new(arity: usize) -> Self {
+        Capture {
+            arity,
+            records: Vec::new(),
+        }
+    }
+    pub fn record(&mut self, tick: usize, packet: &Packet) {
+        self.records.push(Record {
+            tick,
+            packet: packet.clone(),
+        });
+    }
+    pub fn summary(&self, nat: i64) -> Summary {
+        let mut summary = Summary {
+            nat,
+            ..Default::default()
+        };
+        for Record { tick, packet } in self.records.iter() {
+            *summary.sent.entry(packet.source).or_insert(0) += 1;
+            *summary.received.entry(packet.destination).or_insert(0) += 1;
+            if packet.source == nat {
+                summary
+                    .nat_deliveries
+                    .push((*tick, packet.destination, packet.values.clone()));
+            }
+        }
+        summary
+    }
+}
+
+impl fmt::Display for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "arity {}", self.arity)?;
+        for Record { tick, packet } in self.records.iter() {
+            writeln!(
+                f,
+                "packet {} {} {} {}",
+                tick,
+                packet.source,
+                packet.destination,
+                join(packet.values.iter().cloned())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Capture {
+    type Err = ComputerError;
+
+    fn from_str(text: &str) -> Result<Self, ComputerError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+        match lines.next() {
+            Some((_, HEADER)) => (),
+            Some((_, header)) => return Err(parse_error(1, 1, header)),
+            None => return Err(parse_error(1, 1, "")),
+        }
+        let mut capture = Capture::default();
+        for (line, text) in lines {
+            let mut words = text.split(' ');
+            let key = words.next().unwrap();
+            let mut column = key.len() + 2;
+            let mut fields = Vec::new();
+            for word in words.by_ref().take(3) {
+                fields.push((word, column));
+                column += word.len() + 1;
+            }
+            let values = words.next().unwrap_or("");
+            match (key, fields.as_slice()) {
+                ("arity", &[(arity, column)]) => {
+                    capture.arity = parse_number(arity, line, column)? as usize
+                }
+                (
+                    "packet",
+                    &[(tick, tick_column), (source, source_column), (destination, destination_column)],
+                ) => capture.records.push(Record {
+                    tick: parse_number(tick, line, tick_column)? as usize,
+                    packet: Packet {
+                        source: parse_number(source, line, source_column)?,
+                        destination: parse_number(destination, line, destination_column)?,
+                        values: parse_list(values, line, column)?,
+                    },
+                }),
+                ("", []) => (),
+                _ => return Err(parse_error(line, 1, text)),
+            }
+        }
+        Ok(capture)
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Summary {
+    pub nat: i64,
+    pub sent: BTreeMap<i64, usize>,
+    pub received: BTreeMap<i64, usize>,
+    // tick, destination and payload of every packet the nat sent
+    pub nat_deliveries: Vec<(usize, i64, Vec<i64>)>,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>8} {:>8} {:>8}", "address", "sent", "received")?;
+        let mut addresses: Vec<i64> = self
+            .sent
+            .keys()
+            .chain(self.received.keys())
+            .cloned()
+            .collect();
+        addresses.sort();
+        addresses.dedup();
+        for address in addresses {
+            writeln!(
+                f,
+                "{:>8} {:>8} {:>8}",
+                address,
+                self.sent.get(&address).unwrap_or(&0),
+                self.received.get(&address).unwrap_or(&0)
+            )?;
+        }
+        writeln!(
+            f,
+            "nat {} deliveries: {}",
+            self.nat,
+            self.nat_deliveries.len()
+        )?;
+        for (tick, destination, values) in self.nat_deliveries.iter() {
+            writeln!(
+                f,
+                "  tick {} to {}: {}",
+                tick,
+                destination,
+                join(values.iter().cloned())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// feeds a single computer the address and then every packet the capture delivered to that
+// address, reading the empty input until the node goes quiet before each delivery the way the
+// network would. returns the packets the computer sent in response
+pub fn replay<M: Memory>(
+    capture: &Capture,
+    computer: &mut Computer<M>,
+    address: i64,
+    empty_input: i64,
+) -> Result<Vec<Packet>, ComputerError> {
+    computer.yield_on_empty = true;
+    computer.add_input(&[address]);
+    let mut sent = Vec::new();
+    let mut outputs = Vec::new();
+    let incoming = capture
+        .records
+        .iter()
+        .filter(|record| record.packet.destination == address);
+    for record in incoming {
+        if !drain(
+            computer,
+            capture.arity,
+            address,
+            empty_input,
+            &mut outputs,
+            &mut sent,
+        )? {
+            return Ok(sent);
+        }
+        computer.add_input(&record.packet.values);
+    }
+    drain(
+        computer,
+        capture.arity,
+        address,
+        empty_input,
+        &mut outputs,
+        &mut sent,
+    )?;
+    Ok(sent)
+}
+
+// runs until the computer reads the empty input without sending anything, returns false if
+// it halted instead
+fn drain<M: Memory>(
+    computer: &mut Computer<M>,
+    arity: usize,
+    address: i64,
+    empty_input: i64,
+    outputs: &mut Vec<i64>,
+    sent: &mut Vec<Packet>,
+) -> Result<bool, ComputerError> {
+    loop {
+        let starved = computer.input_queue.is_empty();
+        if starved {
+            computer.add_input(&[empty_input]);
+        }
+        let mut quiet = true;
+        loop {
+            match computer.run()? {
+                State::Output(value) => {
+                    quiet = false;
+                    outputs.push(value);
+                    if outputs.len() == arity + 1 {
+                        let values = outputs.split_off(1);
+                        sent.push(Packet {
+                            source: address,
+                            destination: outputs.pop().unwrap(),
+                            values,
+                        });
+                    }
+                }
+                State::NeedsInput => break,
+                State::Halted => return Ok(false),
+            }
+        }
+        if starved && quiet {
+            return Ok(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::network::{Nat, Network};
+
+    fn day_23() -> (Vec<i64>, Capture) {
+        let program = Computer::load_data(23).unwrap();
+        let mut network = Network::new(&program, 50, Nat::new(255)).with_capture();
+        network.run().unwrap();
+        let capture = network.capture().unwrap().clone();
+        (program, capture)
+    }
+
+    #[test]
+    fn test_capture() {
+        let (_, capture) = day_23();
+        assert_eq!(capture.arity, 2);
+        assert!(capture
+            .records
+            .windows(2)
+            .all(|pair| pair[0].tick <= pair[1].tick));
+        let text = capture.to_string();
+        assert!(text.starts_with("intcode-capture 1\narity 2\npacket "));
+        assert_eq!(text.parse::<Capture>(), Ok(capture.clone()));
+
+        let summary = capture.summary(255);
+        assert_eq!(summary.sent.values().sum::<usize>(), capture.records.len());
+        assert_eq!(
+            summary.received.values().sum::<usize>(),
+            capture.records.len()
+        );
+        // the last delivery is the one the nat repeated
+        let (_, destination, values) = summary.nat_deliveries.last().unwrap();
+        assert_eq!((*destination, values[1]), (0, 13758));
+        assert!(summary.to_string().contains(&format!(
+            "nat 255 deliveries: {}\n",
+            summary.nat_deliveries.len()
+        )));
+    }
+
+    #[test]
+    fn test_replay() {
+        let (program, capture) = day_23();
+        for &address in [0, 1, 17, 49].iter() {
+            let mut computer = Computer::new(&program, &[]);
+            let expected: Vec<Packet> = capture
+                .records
+                .iter()
+                .map(|record| record.packet.clone())
+                .filter(|packet| packet.source == address)
+                .collect();
+            assert_eq!(
+                replay(&capture, &mut computer, address, -1),
+                Ok(expected),
+                "node {}",
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            format!("{}\npacket 1 2 x 4", HEADER).parse::<Capture>(),
+            Err(parse_error(2, 12, "x"))
+        );
+        assert_eq!(
+            format!("{}\npacket 1 2", HEADER).parse::<Capture>(),
+            Err(parse_error(2, 1, "packet 1 2"))
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+use super::{Computer, ComputerError, State};
+
+#[derive(Debug, Clone)]
+struct Node {
+    program: Vec<i64>,
+    input: Vec<i64>,
+}
+
+// computers wired together, each running on its own thread. a node's outputs are sent to every
+// node it is connected to, in the order the connections were made
+#[derive(Debug, Clone, Default)]
+pub struct Circuit {
+    nodes: Vec<Node>,
+    edges: Vec<(usize, usize)>,
+}
+
+#[derive(Debug)]
+struct Queues {
+    input: Vec<VecDeque<i64>>,
+    finished: Vec<bool>,
+    running: usize,
+    waiting: usize,
+    deadlocked: bool,
+}
+
+// the values in flight between nodes, and enough about who is waiting on them to tell when
+// nothing can ever move again
+#[derive(Debug)]
+struct Wires {
+    queues: Mutex<Queues>,
+    changed: Condvar,
+    sources: Vec<Vec<usize>>,
+}
+
+impl Wires {
+    // None once no node that could still send to this one is running, or on deadlock
+    fn read(&self, node: usize) -> Option<i64> {
+        let mut queues = self.queues.lock().unwrap();
+        loop {
+            if let Some(value) = queues.input[node].pop_front() {
+                return Some(value);
+            }
+            let connected = self.sources[node].iter().any(|&s| !queues.finished[s]);
+            if queues.deadlocked || !connected {
+                return None;
+            }
+            // everyone else still running is waiting too, and nothing is queued for any of them
+            let pending =
+                (0..queues.input.len()).any(|i| !queues.finished[i] && !queues.input[i].is_empty());
+            if queues.waiting + 1 == queues.running && !pending {
+                queues.deadlocked = true;
+                self.changed.notify_all();
+                return None;
+            }
+            queues.waiting += 1;
+            queues = self.changed.wait(queues).unwrap();
+            queues.waiting -= 1;
+        }
+    }
+    fn write(&self, targets: &[usize], value: i64) {
+        let mut queues = self.queues.lock().unwrap();
+        for &target in targets {
+            queues.input[target].push_back(value);
+        }
+        self.changed.notify_all();
+    }
+    fn finish(&self, node: usize) {
+        let mut queues = self.queues.lock().unwrap();
+        queues.finished[node] = true;
+        queues.running -= 1;
+        self.changed.notify_all();
+    }
+}
+
+impl Circuit {
+    pub fn new() -> Self {
+        Circuit::default()
+    }
+    // each setting becomes the first input of its own node, fed in order
+    pub fn pipeline(program: &[i64], settings: &[i64]) -> Self {
+        let mut circuit = Circuit::new();
+        for &setting in settings {
+            let node = circuit.add_node(program, &[setting]);
+            if node > 0 {
+                circuit.connect(node - 1, node);
+            }
+        }
+        circuit
+    }
+    pub fn ring(program: &[i64], settings: &[i64]) -> Self {
+        let mut circuit = Circuit::pipeline(program, settings);
+        if !settings.is_empty() {
+            circuit.connect(settings.len() - 1, 0);
+        }
+        circuit
+    }
+    pub fn add_node(&mut self, program: &[i64], input: &[i64]) -> usize {
+        self.nodes.push(Node {
+            program: program.to_vec(),
+            input: input.to_vec(),
+        });
+        self.nodes.len() - 1
+    }
+    pub fn connect(&mut self, from: usize, to: usize) {
+        self.edges.push((from, to));
+    }
+    // queues extra input for a node after its own, e.g. the signal that starts a pipeline
+    pub fn add_input(&mut self, node: usize, input: &[i64]) {
+        self.nodes[node].input.extend(input);
+    }
+    // runs until every node has halted or is waiting on nodes that have, and returns everything
+    // each node output. fails with ComputerError::Deadlock if every node still running ends up
+    // waiting for input
+    pub fn run(&self) -> Result<Vec<Vec<i64>>, ComputerError> {
+        let wires = Wires {
+            queues: Mutex::new(Queues {
+                input: self
+                    .nodes
+                    .iter()
+                    .map(|node| node.input.iter().cloned().collect())
+                    .collect(),
+                finished: vec![false; self.nodes.len()],
+                running: self.nodes.len(),
+                waiting: 0,
+                deadlocked: false,
+            }),
+            changed: Condvar::new(),
+            sources: (0..self.nodes.len())
+                .map(|i| {
+                    self.edges
+                        .iter()
+                        .filter(|&&(_, to)| to == i)
+                        .map(|&(from, _)| from)
+                        .collect()
+                })
+                .collect(),
+        };
+        let wires = &wires;
+        thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .nodes
+                .iter()
+                .enumerate()
+                .map(|(i, node)| {
+                    let targets: Vec<usize> = self
+                        .edges
+                        .iter()
+                        .filter(|&&(from, _)| from == i)
+                        .map(|&(_, to)| to)
+                        .collect();
+                    scope.spawn(move || {
+                        let mut outputs = Vec::new();
+                        let mut input = || wires.read(i);
+                        let mut output = |value| {
+                            outputs.push(value);
+                            wires.write(&targets, value);
+                        };
+                        let result =
+                            Computer::new(&node.program, &[]).run_devices(&mut input, &mut output);
+                        wires.finish(i);
+                        match result? {
+                            State::NeedsInput if wires.queues.lock().unwrap().deadlocked => {
+                                Err(ComputerError::Deadlock)
+                            }
+                            _ => Ok(outputs),
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    // adds its setting to every input until it reads a zero
+    fn adder() -> Vec<i64> {
+        assemble(
+            "
+                in setting
+            loop:
+                in x
+                jz x, #done
+                add x, setting, x
+                out x
+                jnz #1, #loop
+            done:
+                hlt
+            setting: db 0
+            x:       db 0
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let mut circuit = Circuit::pipeline(&adder(), &[1, 10, 100]);
+        circuit.add_input(0, &[1, 2, 0]);
+        assert_eq!(
+            circuit.run(),
+            Ok(vec![vec![2, 3], vec![12, 13], vec![112, 113]])
+        );
+    }
+
+    #[test]
+    fn test_fan_out() {
+        // the first node feeds two independent branches
+        let mut circuit = Circuit::new();
+        let source = circuit.add_node(&adder(), &[1, 5, 6, 0]);
+        let left = circuit.add_node(&adder(), &[10]);
+        let right = circuit.add_node(&adder(), &[20]);
+        circuit.connect(source, left);
+        circuit.connect(source, right);
+        assert_eq!(
+            circuit.run(),
+            Ok(vec![vec![6, 7], vec![16, 17], vec![26, 27]])
+        );
+    }
+
+    #[test]
+    fn test_deadlock() {
+        // nothing starts the ring, so every node waits on the one before it forever
+        let circuit = Circuit::ring(&adder(), &[1, 2, 3]);
+        assert_eq!(circuit.run(), Err(ComputerError::Deadlock));
+        // waiting on a node that halted isn't a deadlock
+        let mut circuit = Circuit::ring(&adder(), &[1, 2]);
+        circuit.add_input(0, &[5, 0]);
+        assert_eq!(circuit.run(), Ok(vec![vec![6], vec![8]]));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut circuit = Circuit::pipeline(&adder(), &[1]);
+        circuit.add_node(&[42], &[]);
+        circuit.connect(0, 1);
+        assert_eq!(
+            circuit.run(),
+            Err(ComputerError::UnknownOpCode {
+                opcode: 42,
+                address: 0
+            })
+        );
+    }
+}
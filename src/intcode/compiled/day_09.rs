@@ -0,0 +1,565 @@
+// generated by intcode_transpile, do not edit
+use crate::intcode::transpile::{Compiled, Machine};
+use crate::intcode::{ComputerError, State};
+
+pub struct Day09;
+
+impl Compiled for Day09 {
+    const PROGRAM: &'static [i64] = &[
+        1102, 34463338, 34463338, 63, 1007, 63, 34463338, 63, 1005, 63, 53, 1101, 0, 3, 1000, 109,
+        988, 209, 12, 9, 1000, 209, 6, 209, 3, 203, 0, 1008, 1000, 1, 63, 1005,
+        63, 65, 1008, 1000, 2, 63, 1005, 63, 904, 1008, 1000, 0, 63, 1005, 63, 58,
+        4, 25, 104, 0, 99, 4, 0, 104, 0, 99, 4, 17, 104, 0, 99, 0,
+        0, 1101, 0, 252, 1023, 1101, 0, 0, 1020, 1102, 1, 39, 1013, 1102, 1, 234,
+        1029, 1102, 26, 1, 1016, 1101, 37, 0, 1005, 1101, 0, 27, 1011, 1101, 21, 0,
+        1000, 1101, 0, 29, 1019, 1101, 35, 0, 1003, 1102, 22, 1, 1007, 1102, 1, 32,
+        1001, 1101, 1, 0, 1021, 1102, 1, 216, 1027, 1102, 30, 1, 1012, 1102, 1, 24,
+        1009, 1101, 36, 0, 1002, 1101, 0, 31, 1010, 1101, 0, 243, 1028, 1102, 787, 1,
+        1024, 1102, 255, 1, 1022, 1102, 33, 1, 1017, 1102, 1, 23, 1004, 1102, 778, 1,
+        1025, 1102, 1, 28, 1008, 1101, 0, 223, 1026, 1102, 1, 25, 1015, 1101, 0, 20,
+        1006, 1102, 34, 1, 1014, 1101, 38, 0, 1018, 109, -4, 1202, 5, 1, 63, 1008,
+        63, 32, 63, 1005, 63, 203, 4, 187, 1106, 0, 207, 1001, 64, 1, 64, 1002,
+        64, 2, 64, 109, 37, 2106, 0, -6, 1001, 64, 1, 64, 1106, 0, 225, 4,
+        213, 1002, 64, 2, 64, 109, 3, 2106, 0, -8, 4, 231, 1001, 64, 1, 64,
+        1105, 1, 243, 1002, 64, 2, 64, 109, -12, 2105, 1, -1, 1105, 1, 261, 4,
+        249, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -13, 2102, 1, -3, 63, 1008,
+        63, 31, 63, 1005, 63, 285, 1001, 64, 1, 64, 1106, 0, 287, 4, 267, 1002,
+        64, 2, 64, 109, 6, 21102, 40, 1, 0, 1008, 1017, 40, 63, 1005, 63, 313,
+        4, 293, 1001, 64, 1, 64, 1105, 1, 313, 1002, 64, 2, 64, 109, -10, 2107,
+        31, -6, 63, 1005, 63, 331, 4, 319, 1105, 1, 335, 1001, 64, 1, 64, 1002,
+        64, 2, 64, 109, -6, 2102, 1, 7, 63, 1008, 63, 28, 63, 1005, 63, 357,
+        4, 341, 1105, 1, 361, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 2, 21107,
+        41, 40, 8, 1005, 1011, 377, 1106, 0, 383, 4, 367, 1001, 64, 1, 64, 1002,
+        64, 2, 64, 109, -1, 1201, 2, 0, 63, 1008, 63, 26, 63, 1005, 63, 403,
+        1106, 0, 409, 4, 389, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 22, 1205,
+        -4, 425, 1001, 64, 1, 64, 1105, 1, 427, 4, 415, 1002, 64, 2, 64, 109,
+        -9, 21101, 42, 0, 3, 1008, 1018, 39, 63, 1005, 63, 451, 1001, 64, 1, 64,
+        1105, 1, 453, 4, 433, 1002, 64, 2, 64, 109, 3, 21107, 43, 44, 0, 1005,
+        1018, 475, 4, 459, 1001, 64, 1, 64, 1105, 1, 475, 1002, 64, 2, 64, 109,
+        -7, 21101, 44, 0, 0, 1008, 1011, 44, 63, 1005, 63, 497, 4, 481, 1105, 1,
+        501, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 17, 1206, -7, 513, 1105, 1,
+        519, 4, 507, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -24, 1207, 5, 25,
+        63, 1005, 63, 537, 4, 525, 1105, 1, 541, 1001, 64, 1, 64, 1002, 64, 2,
+        64, 109, 7, 21108, 45, 43, 2, 1005, 1013, 557, 1106, 0, 563, 4, 547, 1001,
+        64, 1, 64, 1002, 64, 2, 64, 109, -5, 1207, -3, 34, 63, 1005, 63, 583,
+        1001, 64, 1, 64, 1106, 0, 585, 4, 569, 1002, 64, 2, 64, 109, 5, 21108,
+        46, 46, 5, 1005, 1016, 607, 4, 591, 1001, 64, 1, 64, 1105, 1, 607, 1002,
+        64, 2, 64, 109, -12, 2108, 20, 8, 63, 1005, 63, 627, 1001, 64, 1, 64,
+        1105, 1, 629, 4, 613, 1002, 64, 2, 64, 109, 24, 1206, -3, 647, 4, 635,
+        1001, 64, 1, 64, 1105, 1, 647, 1002, 64, 2, 64, 109, -30, 2108, 32, 8,
+        63, 1005, 63, 665, 4, 653, 1106, 0, 669, 1001, 64, 1, 64, 1002, 64, 2,
+        64, 109, 22, 1208, -9, 20, 63, 1005, 63, 691, 4, 675, 1001, 64, 1, 64,
+        1106, 0, 691, 1002, 64, 2, 64, 109, -4, 21102, 47, 1, 3, 1008, 1014, 49,
+        63, 1005, 63, 715, 1001, 64, 1, 64, 1105, 1, 717, 4, 697, 1002, 64, 2,
+        64, 109, -10, 2101, 0, 1, 63, 1008, 63, 36, 63, 1005, 63, 743, 4, 723,
+        1001, 64, 1, 64, 1105, 1, 743, 1002, 64, 2, 64, 109, 16, 1201, -9, 0,
+        63, 1008, 63, 28, 63, 1005, 63, 769, 4, 749, 1001, 64, 1, 64, 1105, 1,
+        769, 1002, 64, 2, 64, 109, 2, 2105, 1, 5, 4, 775, 1001, 64, 1, 64,
+        1106, 0, 787, 1002, 64, 2, 64, 109, -5, 1202, -6, 1, 63, 1008, 63, 26,
+        63, 1005, 63, 807, 1106, 0, 813, 4, 793, 1001, 64, 1, 64, 1002, 64, 2,
+        64, 109, -16, 2107, 37, 4, 63, 1005, 63, 833, 1001, 64, 1, 64, 1105, 1,
+        835, 4, 819, 1002, 64, 2, 64, 109, 2, 2101, 0, 1, 63, 1008, 63, 34,
+        63, 1005, 63, 855, 1105, 1, 861, 4, 841, 1001, 64, 1, 64, 1002, 64, 2,
+        64, 109, 19, 1205, 2, 875, 4, 867, 1105, 1, 879, 1001, 64, 1, 64, 1002,
+        64, 2, 64, 109, -2, 1208, -8, 23, 63, 1005, 63, 899, 1001, 64, 1, 64,
+        1106, 0, 901, 4, 885, 4, 64, 99, 21101, 0, 27, 1, 21102, 915, 1, 0,
+        1106, 0, 922, 21201, 1, 61455, 1, 204, 1, 99, 109, 3, 1207, -2, 3, 63,
+        1005, 63, 964, 21201, -2, -1, 1, 21102, 942, 1, 0, 1105, 1, 922, 22102, 1,
+        1, -1, 21201, -2, -3, 1, 21102, 1, 957, 0, 1105, 1, 922, 22201, 1, -1,
+        -2, 1106, 0, 968, 22101, 0, -2, -2, 109, -3, 2105, 1, 0,
+    ];
+    const INSTRUCTIONS: &'static [(usize, usize)] = &[
+        (0, 4), (4, 4), (8, 3), (11, 4), (15, 2), (17, 2), (19, 2), (21, 2),
+        (23, 2), (25, 2), (27, 4), (31, 3), (34, 4), (38, 3), (41, 4), (45, 3),
+        (48, 2), (50, 2), (52, 1), (53, 2), (55, 2), (57, 1), (58, 2), (60, 2),
+        (62, 1), (65, 4), (69, 4), (73, 4), (77, 4), (81, 4), (85, 4), (89, 4),
+        (93, 4), (97, 4), (101, 4), (105, 4), (109, 4), (113, 4), (117, 4), (121, 4),
+        (125, 4), (129, 4), (133, 4), (137, 4), (141, 4), (145, 4), (149, 4), (153, 4),
+        (157, 4), (161, 4), (165, 4), (169, 4), (173, 4), (177, 4), (181, 4), (185, 2),
+        (187, 4), (191, 4), (195, 3), (198, 2), (200, 3), (203, 4), (207, 4), (211, 2),
+        (213, 3), (904, 4), (908, 4), (912, 3), (915, 4), (919, 2), (921, 1), (922, 2),
+        (924, 4), (928, 3), (931, 4), (935, 4), (939, 3), (942, 4), (946, 4), (950, 4),
+        (954, 3), (957, 4), (961, 3), (964, 4), (968, 2), (970, 3),
+    ];
+
+    fn execute(m: &mut Machine) -> Result<Option<State>, ComputerError> {
+        while let Some(pos) = m.pos() {
+            match pos {
+                0 => {
+                    let a = 34463338;
+                    let b = 34463338;
+                    m.set(63, m.mul(a, b)?)?;
+                    m.jump(4);
+                }
+                4 => {
+                    let a = m.get(63)?;
+                    let b = 34463338;
+                    m.set(63, (a < b) as i64)?;
+                    m.jump(8);
+                }
+                8 => {
+                    if m.get(63)? != 0 {
+                        m.jump(53);
+                    } else {
+                        m.jump(11);
+                    }
+                }
+                11 => {
+                    let a = 0;
+                    let b = 3;
+                    m.set(1000, m.add(a, b)?)?;
+                    m.jump(15);
+                }
+                15 => {
+                    let a = 988;
+                    m.jump(17);
+                    m.offset_relative_base(a);
+                }
+                17 => {
+                    let a = m.get(m.relative(12))?;
+                    m.jump(19);
+                    m.offset_relative_base(a);
+                }
+                19 => {
+                    let a = m.get(1000)?;
+                    m.jump(21);
+                    m.offset_relative_base(a);
+                }
+                21 => {
+                    let a = m.get(m.relative(6))?;
+                    m.jump(23);
+                    m.offset_relative_base(a);
+                }
+                23 => {
+                    let a = m.get(m.relative(3))?;
+                    m.jump(25);
+                    m.offset_relative_base(a);
+                }
+                25 => {
+                    if !m.input(m.relative(0))? {
+                        return Ok(Some(State::NeedsInput));
+                    }
+                    m.jump(27);
+                }
+                27 => {
+                    let a = m.get(1000)?;
+                    let b = 1;
+                    m.set(63, (a == b) as i64)?;
+                    m.jump(31);
+                }
+                31 => {
+                    if m.get(63)? != 0 {
+                        m.jump(65);
+                    } else {
+                        m.jump(34);
+                    }
+                }
+                34 => {
+                    let a = m.get(1000)?;
+                    let b = 2;
+                    m.set(63, (a == b) as i64)?;
+                    m.jump(38);
+                }
+                38 => {
+                    if m.get(63)? != 0 {
+                        m.jump(904);
+                    } else {
+                        m.jump(41);
+                    }
+                }
+                41 => {
+                    let a = m.get(1000)?;
+                    let b = 0;
+                    m.set(63, (a == b) as i64)?;
+                    m.jump(45);
+                }
+                45 => {
+                    if m.get(63)? != 0 {
+                        m.jump(58);
+                    } else {
+                        m.jump(48);
+                    }
+                }
+                48 => {
+                    let a = m.get(25)?;
+                    m.jump(50);
+                    return Ok(Some(State::Output(a)));
+                }
+                50 => {
+                    let a = 0;
+                    m.jump(52);
+                    return Ok(Some(State::Output(a)));
+                }
+                52 => {
+                    return Ok(Some(State::Halted));
+                }
+                53 => {
+                    let a = m.get(0)?;
+                    m.jump(55);
+                    return Ok(Some(State::Output(a)));
+                }
+                55 => {
+                    let a = 0;
+                    m.jump(57);
+                    return Ok(Some(State::Output(a)));
+                }
+                57 => {
+                    return Ok(Some(State::Halted));
+                }
+                58 => {
+                    let a = m.get(17)?;
+                    m.jump(60);
+                    return Ok(Some(State::Output(a)));
+                }
+                60 => {
+                    let a = 0;
+                    m.jump(62);
+                    return Ok(Some(State::Output(a)));
+                }
+                62 => {
+                    return Ok(Some(State::Halted));
+                }
+                65 => {
+                    let a = 0;
+                    let b = 252;
+                    m.set(1023, m.add(a, b)?)?;
+                    m.jump(69);
+                }
+                69 => {
+                    let a = 0;
+                    let b = 0;
+                    m.set(1020, m.add(a, b)?)?;
+                    m.jump(73);
+                }
+                73 => {
+                    let a = 1;
+                    let b = 39;
+                    m.set(1013, m.mul(a, b)?)?;
+                    m.jump(77);
+                }
+                77 => {
+                    let a = 1;
+                    let b = 234;
+                    m.set(1029, m.mul(a, b)?)?;
+                    m.jump(81);
+                }
+                81 => {
+                    let a = 26;
+                    let b = 1;
+                    m.set(1016, m.mul(a, b)?)?;
+                    m.jump(85);
+                }
+                85 => {
+                    let a = 37;
+                    let b = 0;
+                    m.set(1005, m.add(a, b)?)?;
+                    m.jump(89);
+                }
+                89 => {
+                    let a = 0;
+                    let b = 27;
+                    m.set(1011, m.add(a, b)?)?;
+                    m.jump(93);
+                }
+                93 => {
+                    let a = 21;
+                    let b = 0;
+                    m.set(1000, m.add(a, b)?)?;
+                    m.jump(97);
+                }
+                97 => {
+                    let a = 0;
+                    let b = 29;
+                    m.set(1019, m.add(a, b)?)?;
+                    m.jump(101);
+                }
+                101 => {
+                    let a = 35;
+                    let b = 0;
+                    m.set(1003, m.add(a, b)?)?;
+                    m.jump(105);
+                }
+                105 => {
+                    let a = 22;
+                    let b = 1;
+                    m.set(1007, m.mul(a, b)?)?;
+                    m.jump(109);
+                }
+                109 => {
+                    let a = 1;
+                    let b = 32;
+                    m.set(1001, m.mul(a, b)?)?;
+                    m.jump(113);
+                }
+                113 => {
+                    let a = 1;
+                    let b = 0;
+                    m.set(1021, m.add(a, b)?)?;
+                    m.jump(117);
+                }
+                117 => {
+                    let a = 1;
+                    let b = 216;
+                    m.set(1027, m.mul(a, b)?)?;
+                    m.jump(121);
+                }
+                121 => {
+                    let a = 30;
+                    let b = 1;
+                    m.set(1012, m.mul(a, b)?)?;
+                    m.jump(125);
+                }
+                125 => {
+                    let a = 1;
+                    let b = 24;
+                    m.set(1009, m.mul(a, b)?)?;
+                    m.jump(129);
+                }
+                129 => {
+                    let a = 36;
+                    let b = 0;
+                    m.set(1002, m.add(a, b)?)?;
+                    m.jump(133);
+                }
+                133 => {
+                    let a = 0;
+                    let b = 31;
+                    m.set(1010, m.add(a, b)?)?;
+                    m.jump(137);
+                }
+                137 => {
+                    let a = 0;
+                    let b = 243;
+                    m.set(1028, m.add(a, b)?)?;
+                    m.jump(141);
+                }
+                141 => {
+                    let a = 787;
+                    let b = 1;
+                    m.set(1024, m.mul(a, b)?)?;
+                    m.jump(145);
+                }
+                145 => {
+                    let a = 255;
+                    let b = 1;
+                    m.set(1022, m.mul(a, b)?)?;
+                    m.jump(149);
+                }
+                149 => {
+                    let a = 33;
+                    let b = 1;
+                    m.set(1017, m.mul(a, b)?)?;
+                    m.jump(153);
+                }
+                153 => {
+                    let a = 1;
+                    let b = 23;
+                    m.set(1004, m.mul(a, b)?)?;
+                    m.jump(157);
+                }
+                157 => {
+                    let a = 778;
+                    let b = 1;
+                    m.set(1025, m.mul(a, b)?)?;
+                    m.jump(161);
+                }
+                161 => {
+                    let a = 1;
+                    let b = 28;
+                    m.set(1008, m.mul(a, b)?)?;
+                    m.jump(165);
+                }
+                165 => {
+                    let a = 0;
+                    let b = 223;
+                    m.set(1026, m.add(a, b)?)?;
+                    m.jump(169);
+                }
+                169 => {
+                    let a = 1;
+                    let b = 25;
+                    m.set(1015, m.mul(a, b)?)?;
+                    m.jump(173);
+                }
+                173 => {
+                    let a = 0;
+                    let b = 20;
+                    m.set(1006, m.add(a, b)?)?;
+                    m.jump(177);
+                }
+                177 => {
+                    let a = 34;
+                    let b = 1;
+                    m.set(1014, m.mul(a, b)?)?;
+                    m.jump(181);
+                }
+                181 => {
+                    let a = 38;
+                    let b = 0;
+                    m.set(1018, m.add(a, b)?)?;
+                    m.jump(185);
+                }
+                185 => {
+                    let a = -4;
+                    m.jump(187);
+                    m.offset_relative_base(a);
+                }
+                187 => {
+                    let a = m.get(m.relative(5))?;
+                    let b = 1;
+                    m.set(63, m.mul(a, b)?)?;
+                    m.jump(191);
+                }
+                191 => {
+                    let a = m.get(63)?;
+                    let b = 32;
+                    m.set(63, (a == b) as i64)?;
+                    m.jump(195);
+                }
+                195 => {
+                    if m.get(63)? != 0 {
+                        m.jump(203);
+                    } else {
+                        m.jump(198);
+                    }
+                }
+                198 => {
+                    let a = m.get(187)?;
+                    m.jump(200);
+                    return Ok(Some(State::Output(a)));
+                }
+                200 => {
+                    m.jump(207);
+                }
+                203 => {
+                    let a = m.get(64)?;
+                    let b = 1;
+                    m.set(64, m.add(a, b)?)?;
+                    m.jump(207);
+                }
+                207 => {
+                    let a = m.get(64)?;
+                    let b = 2;
+                    m.set(64, m.mul(a, b)?)?;
+                    m.jump(211);
+                }
+                211 => {
+                    let a = 37;
+                    m.jump(213);
+                    m.offset_relative_base(a);
+                }
+                213 => {
+                    m.jump(m.get(m.relative(-6))?);
+                }
+                904 => {
+                    let a = 0;
+                    let b = 27;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(908);
+                }
+                908 => {
+                    let a = 915;
+                    let b = 1;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(912);
+                }
+                912 => {
+                    m.jump(922);
+                }
+                915 => {
+                    let a = m.get(m.relative(1))?;
+                    let b = 61455;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(919);
+                }
+                919 => {
+                    let a = m.get(m.relative(1))?;
+                    m.jump(921);
+                    return Ok(Some(State::Output(a)));
+                }
+                921 => {
+                    return Ok(Some(State::Halted));
+                }
+                922 => {
+                    let a = 3;
+                    m.jump(924);
+                    m.offset_relative_base(a);
+                }
+                924 => {
+                    let a = m.get(m.relative(-2))?;
+                    let b = 3;
+                    m.set(63, (a < b) as i64)?;
+                    m.jump(928);
+                }
+                928 => {
+                    if m.get(63)? != 0 {
+                        m.jump(964);
+                    } else {
+                        m.jump(931);
+                    }
+                }
+                931 => {
+                    let a = m.get(m.relative(-2))?;
+                    let b = -1;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(935);
+                }
+                935 => {
+                    let a = 942;
+                    let b = 1;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(939);
+                }
+                939 => {
+                    m.jump(922);
+                }
+                942 => {
+                    let a = 1;
+                    let b = m.get(m.relative(1))?;
+                    m.set(m.relative(-1), m.mul(a, b)?)?;
+                    m.jump(946);
+                }
+                946 => {
+                    let a = m.get(m.relative(-2))?;
+                    let b = -3;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(950);
+                }
+                950 => {
+                    let a = 1;
+                    let b = 957;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(954);
+                }
+                954 => {
+                    m.jump(922);
+                }
+                957 => {
+                    let a = m.get(m.relative(1))?;
+                    let b = m.get(m.relative(-1))?;
+                    m.set(m.relative(-2), m.add(a, b)?)?;
+                    m.jump(961);
+                }
+                961 => {
+                    m.jump(968);
+                }
+                964 => {
+                    let a = 0;
+                    let b = m.get(m.relative(-2))?;
+                    m.set(m.relative(-2), m.add(a, b)?)?;
+                    m.jump(968);
+                }
+                968 => {
+                    let a = -3;
+                    m.jump(970);
+                    m.offset_relative_base(a);
+                }
+                970 => {
+                    m.jump(m.get(m.relative(0))?);
+                }
+                _ => break,
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,694 @@
+// generated by intcode_transpile, do not edit
+use crate::intcode::transpile::{Compiled, Machine};
+use crate::intcode::{ComputerError, State};
+
+pub struct Day19;
+
+impl Compiled for Day19 {
+    const PROGRAM: &'static [i64] = &[
+        109, 424, 203, 1, 21102, 1, 11, 0, 1106, 0, 282, 21101, 0, 18, 0, 1105,
+        1, 259, 1201, 1, 0, 221, 203, 1, 21101, 31, 0, 0, 1105, 1, 282, 21102,
+        38, 1, 0, 1105, 1, 259, 21001, 23, 0, 2, 21201, 1, 0, 3, 21101, 1,
+        0, 1, 21102, 57, 1, 0, 1106, 0, 303, 2102, 1, 1, 222, 21001, 221, 0,
+        3, 20102, 1, 221, 2, 21101, 259, 0, 1, 21102, 80, 1, 0, 1106, 0, 225,
+        21101, 0, 167, 2, 21101, 0, 91, 0, 1105, 1, 303, 2102, 1, 1, 223, 20102,
+        1, 222, 4, 21102, 1, 259, 3, 21102, 1, 225, 2, 21102, 225, 1, 1, 21102,
+        1, 118, 0, 1106, 0, 225, 21001, 222, 0, 3, 21102, 1, 93, 2, 21101, 0,
+        133, 0, 1105, 1, 303, 21202, 1, -1, 1, 22001, 223, 1, 1, 21101, 148, 0,
+        0, 1105, 1, 259, 2101, 0, 1, 223, 21001, 221, 0, 4, 20102, 1, 222, 3,
+        21102, 21, 1, 2, 1001, 132, -2, 224, 1002, 224, 2, 224, 1001, 224, 3, 224,
+        1002, 132, -1, 132, 1, 224, 132, 224, 21001, 224, 1, 1, 21102, 1, 195, 0,
+        106, 0, 108, 20207, 1, 223, 2, 21001, 23, 0, 1, 21101, -1, 0, 3, 21102,
+        214, 1, 0, 1106, 0, 303, 22101, 1, 1, 1, 204, 1, 99, 0, 0, 0,
+        0, 109, 5, 1202, -4, 1, 249, 21202, -3, 1, 1, 21202, -2, 1, 2, 21201,
+        -1, 0, 3, 21101, 0, 250, 0, 1105, 1, 225, 22101, 0, 1, -4, 109, -5,
+        2106, 0, 0, 109, 3, 22107, 0, -2, -1, 21202, -1, 2, -1, 21201, -1, -1,
+        -1, 22202, -1, -2, -2, 109, -3, 2106, 0, 0, 109, 3, 21207, -2, 0, -1,
+        1206, -1, 294, 104, 0, 99, 22101, 0, -2, -2, 109, -3, 2106, 0, 0, 109,
+        5, 22207, -3, -4, -1, 1206, -1, 346, 22201, -4, -3, -4, 21202, -3, -1, -1,
+        22201, -4, -1, 2, 21202, 2, -1, -1, 22201, -4, -1, 1, 22102, 1, -2, 3,
+        21102, 343, 1, 0, 1105, 1, 303, 1106, 0, 415, 22207, -2, -3, -1, 1206, -1,
+        387, 22201, -3, -2, -3, 21202, -2, -1, -1, 22201, -3, -1, 3, 21202, 3, -1,
+        -1, 22201, -3, -1, 2, 21201, -4, 0, 1, 21102, 384, 1, 0, 1106, 0, 303,
+        1106, 0, 415, 21202, -4, -1, -4, 22201, -4, -3, -4, 22202, -3, -2, -2, 22202,
+        -2, -4, -4, 22202, -3, -2, -3, 21202, -4, -1, -2, 22201, -3, -2, 1, 22102,
+        1, 1, -4, 109, -5, 2105, 1, 0,
+    ];
+    const INSTRUCTIONS: &'static [(usize, usize)] = &[
+        (0, 2), (2, 2), (4, 4), (8, 3), (11, 4), (15, 3), (18, 4), (22, 2),
+        (24, 4), (28, 3), (31, 4), (35, 3), (38, 4), (42, 4), (46, 4), (50, 4),
+        (54, 3), (57, 4), (61, 4), (65, 4), (69, 4), (73, 4), (77, 3), (80, 4),
+        (84, 4), (88, 3), (91, 4), (95, 4), (99, 4), (103, 4), (107, 4), (111, 4),
+        (115, 3), (118, 4), (122, 4), (126, 4), (133, 4), (137, 4), (141, 4), (145, 3),
+        (148, 4), (152, 4), (156, 4), (160, 4), (164, 4), (168, 4), (172, 4), (176, 4),
+        (180, 4), (184, 4), (188, 4), (192, 3), (195, 4), (199, 4), (203, 4), (207, 4),
+        (211, 3), (214, 4), (218, 2), (220, 1), (225, 2), (227, 4), (231, 4), (235, 4),
+        (239, 4), (243, 4), (250, 4), (254, 2), (256, 3), (259, 2), (261, 4), (265, 4),
+        (269, 4), (273, 4), (277, 2), (279, 3), (282, 2), (284, 4), (288, 3), (291, 2),
+        (293, 1), (294, 4), (298, 2), (300, 3), (303, 2), (305, 4), (309, 3), (312, 4),
+        (316, 4), (320, 4), (324, 4), (328, 4), (332, 4), (336, 4), (340, 3), (343, 3),
+        (346, 4), (350, 3), (353, 4), (357, 4), (361, 4), (365, 4), (369, 4), (373, 4),
+        (377, 4), (381, 3), (384, 3), (387, 4), (391, 4), (395, 4), (399, 4), (403, 4),
+        (407, 4), (411, 4), (415, 4), (419, 2), (421, 3),
+    ];
+
+    fn execute(m: &mut Machine) -> Result<Option<State>, ComputerError> {
+        while let Some(pos) = m.pos() {
+            match pos {
+                0 => {
+                    let a = 424;
+                    m.jump(2);
+                    m.offset_relative_base(a);
+                }
+                2 => {
+                    if !m.input(m.relative(1))? {
+                        return Ok(Some(State::NeedsInput));
+                    }
+                    m.jump(4);
+                }
+                4 => {
+                    let a = 1;
+                    let b = 11;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(8);
+                }
+                8 => {
+                    m.jump(282);
+                }
+                11 => {
+                    let a = 0;
+                    let b = 18;
+                    m.set(m.relative(0), m.add(a, b)?)?;
+                    m.jump(15);
+                }
+                15 => {
+                    m.jump(259);
+                }
+                18 => {
+                    let a = m.get(m.relative(1))?;
+                    let b = 0;
+                    m.set(221, m.add(a, b)?)?;
+                    m.jump(22);
+                }
+                22 => {
+                    if !m.input(m.relative(1))? {
+                        return Ok(Some(State::NeedsInput));
+                    }
+                    m.jump(24);
+                }
+                24 => {
+                    let a = 31;
+                    let b = 0;
+                    m.set(m.relative(0), m.add(a, b)?)?;
+                    m.jump(28);
+                }
+                28 => {
+                    m.jump(282);
+                }
+                31 => {
+                    let a = 38;
+                    let b = 1;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(35);
+                }
+                35 => {
+                    m.jump(259);
+                }
+                38 => {
+                    let a = m.get(23)?;
+                    let b = 0;
+                    m.set(m.relative(2), m.add(a, b)?)?;
+                    m.jump(42);
+                }
+                42 => {
+                    let a = m.get(m.relative(1))?;
+                    let b = 0;
+                    m.set(m.relative(3), m.add(a, b)?)?;
+                    m.jump(46);
+                }
+                46 => {
+                    let a = 1;
+                    let b = 0;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(50);
+                }
+                50 => {
+                    let a = 57;
+                    let b = 1;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(54);
+                }
+                54 => {
+                    m.jump(303);
+                }
+                57 => {
+                    let a = 1;
+                    let b = m.get(m.relative(1))?;
+                    m.set(222, m.mul(a, b)?)?;
+                    m.jump(61);
+                }
+                61 => {
+                    let a = m.get(221)?;
+                    let b = 0;
+                    m.set(m.relative(3), m.add(a, b)?)?;
+                    m.jump(65);
+                }
+                65 => {
+                    let a = 1;
+                    let b = m.get(221)?;
+                    m.set(m.relative(2), m.mul(a, b)?)?;
+                    m.jump(69);
+                }
+                69 => {
+                    let a = 259;
+                    let b = 0;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(73);
+                }
+                73 => {
+                    let a = 80;
+                    let b = 1;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(77);
+                }
+                77 => {
+                    m.jump(225);
+                }
+                80 => {
+                    let a = 0;
+                    let b = 167;
+                    m.set(m.relative(2), m.add(a, b)?)?;
+                    m.jump(84);
+                }
+                84 => {
+                    let a = 0;
+                    let b = 91;
+                    m.set(m.relative(0), m.add(a, b)?)?;
+                    m.jump(88);
+                }
+                88 => {
+                    m.jump(303);
+                }
+                91 => {
+                    let a = 1;
+                    let b = m.get(m.relative(1))?;
+                    m.set(223, m.mul(a, b)?)?;
+                    m.jump(95);
+                }
+                95 => {
+                    let a = 1;
+                    let b = m.get(222)?;
+                    m.set(m.relative(4), m.mul(a, b)?)?;
+                    m.jump(99);
+                }
+                99 => {
+                    let a = 1;
+                    let b = 259;
+                    m.set(m.relative(3), m.mul(a, b)?)?;
+                    m.jump(103);
+                }
+                103 => {
+                    let a = 1;
+                    let b = 225;
+                    m.set(m.relative(2), m.mul(a, b)?)?;
+                    m.jump(107);
+                }
+                107 => {
+                    let a = 225;
+                    let b = 1;
+                    m.set(m.relative(1), m.mul(a, b)?)?;
+                    m.jump(111);
+                }
+                111 => {
+                    let a = 1;
+                    let b = 118;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(115);
+                }
+                115 => {
+                    m.jump(225);
+                }
+                118 => {
+                    let a = m.get(222)?;
+                    let b = 0;
+                    m.set(m.relative(3), m.add(a, b)?)?;
+                    m.jump(122);
+                }
+                122 => {
+                    let a = 1;
+                    let b = 93;
+                    m.set(m.relative(2), m.mul(a, b)?)?;
+                    m.jump(126);
+                }
+                126 => {
+                    let a = 0;
+                    let b = 133;
+                    m.set(m.relative(0), m.add(a, b)?)?;
+                    m.jump(130);
+                }
+                133 => {
+                    let a = m.get(m.relative(1))?;
+                    let b = -1;
+                    m.set(m.relative(1), m.mul(a, b)?)?;
+                    m.jump(137);
+                }
+                137 => {
+                    let a = m.get(223)?;
+                    let b = m.get(m.relative(1))?;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(141);
+                }
+                141 => {
+                    let a = 148;
+                    let b = 0;
+                    m.set(m.relative(0), m.add(a, b)?)?;
+                    m.jump(145);
+                }
+                145 => {
+                    m.jump(259);
+                }
+                148 => {
+                    let a = 0;
+                    let b = m.get(m.relative(1))?;
+                    m.set(223, m.add(a, b)?)?;
+                    m.jump(152);
+                }
+                152 => {
+                    let a = m.get(221)?;
+                    let b = 0;
+                    m.set(m.relative(4), m.add(a, b)?)?;
+                    m.jump(156);
+                }
+                156 => {
+                    let a = 1;
+                    let b = m.get(222)?;
+                    m.set(m.relative(3), m.mul(a, b)?)?;
+                    m.jump(160);
+                }
+                160 => {
+                    let a = 21;
+                    let b = 1;
+                    m.set(m.relative(2), m.mul(a, b)?)?;
+                    m.jump(164);
+                }
+                164 => {
+                    let a = m.get(132)?;
+                    let b = -2;
+                    m.set(224, m.add(a, b)?)?;
+                    m.jump(168);
+                }
+                168 => {
+                    let a = m.get(224)?;
+                    let b = 2;
+                    m.set(224, m.mul(a, b)?)?;
+                    m.jump(172);
+                }
+                172 => {
+                    let a = m.get(224)?;
+                    let b = 3;
+                    m.set(224, m.add(a, b)?)?;
+                    m.jump(176);
+                }
+                176 => {
+                    let a = m.get(132)?;
+                    let b = -1;
+                    m.set(132, m.mul(a, b)?)?;
+                    m.jump(180);
+                }
+                180 => {
+                    let a = m.get(224)?;
+                    let b = m.get(132)?;
+                    m.set(224, m.add(a, b)?)?;
+                    m.jump(184);
+                }
+                184 => {
+                    let a = m.get(224)?;
+                    let b = 1;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(188);
+                }
+                188 => {
+                    let a = 1;
+                    let b = 195;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(192);
+                }
+                192 => {
+                    m.jump(m.get(108)?);
+                }
+                195 => {
+                    let a = m.get(m.relative(1))?;
+                    let b = m.get(223)?;
+                    m.set(m.relative(2), (a < b) as i64)?;
+                    m.jump(199);
+                }
+                199 => {
+                    let a = m.get(23)?;
+                    let b = 0;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(203);
+                }
+                203 => {
+                    let a = -1;
+                    let b = 0;
+                    m.set(m.relative(3), m.add(a, b)?)?;
+                    m.jump(207);
+                }
+                207 => {
+                    let a = 214;
+                    let b = 1;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(211);
+                }
+                211 => {
+                    m.jump(303);
+                }
+                214 => {
+                    let a = 1;
+                    let b = m.get(m.relative(1))?;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(218);
+                }
+                218 => {
+                    let a = m.get(m.relative(1))?;
+                    m.jump(220);
+                    return Ok(Some(State::Output(a)));
+                }
+                220 => {
+                    return Ok(Some(State::Halted));
+                }
+                225 => {
+                    let a = 5;
+                    m.jump(227);
+                    m.offset_relative_base(a);
+                }
+                227 => {
+                    let a = m.get(m.relative(-4))?;
+                    let b = 1;
+                    m.set(249, m.mul(a, b)?)?;
+                    m.jump(231);
+                }
+                231 => {
+                    let a = m.get(m.relative(-3))?;
+                    let b = 1;
+                    m.set(m.relative(1), m.mul(a, b)?)?;
+                    m.jump(235);
+                }
+                235 => {
+                    let a = m.get(m.relative(-2))?;
+                    let b = 1;
+                    m.set(m.relative(2), m.mul(a, b)?)?;
+                    m.jump(239);
+                }
+                239 => {
+                    let a = m.get(m.relative(-1))?;
+                    let b = 0;
+                    m.set(m.relative(3), m.add(a, b)?)?;
+                    m.jump(243);
+                }
+                243 => {
+                    let a = 0;
+                    let b = 250;
+                    m.set(m.relative(0), m.add(a, b)?)?;
+                    m.jump(247);
+                }
+                250 => {
+                    let a = 0;
+                    let b = m.get(m.relative(1))?;
+                    m.set(m.relative(-4), m.add(a, b)?)?;
+                    m.jump(254);
+                }
+                254 => {
+                    let a = -5;
+                    m.jump(256);
+                    m.offset_relative_base(a);
+                }
+                256 => {
+                    m.jump(m.get(m.relative(0))?);
+                }
+                259 => {
+                    let a = 3;
+                    m.jump(261);
+                    m.offset_relative_base(a);
+                }
+                261 => {
+                    let a = 0;
+                    let b = m.get(m.relative(-2))?;
+                    m.set(m.relative(-1), (a < b) as i64)?;
+                    m.jump(265);
+                }
+                265 => {
+                    let a = m.get(m.relative(-1))?;
+                    let b = 2;
+                    m.set(m.relative(-1), m.mul(a, b)?)?;
+                    m.jump(269);
+                }
+                269 => {
+                    let a = m.get(m.relative(-1))?;
+                    let b = -1;
+                    m.set(m.relative(-1), m.add(a, b)?)?;
+                    m.jump(273);
+                }
+                273 => {
+                    let a = m.get(m.relative(-1))?;
+                    let b = m.get(m.relative(-2))?;
+                    m.set(m.relative(-2), m.mul(a, b)?)?;
+                    m.jump(277);
+                }
+                277 => {
+                    let a = -3;
+                    m.jump(279);
+                    m.offset_relative_base(a);
+                }
+                279 => {
+                    m.jump(m.get(m.relative(0))?);
+                }
+                282 => {
+                    let a = 3;
+                    m.jump(284);
+                    m.offset_relative_base(a);
+                }
+                284 => {
+                    let a = m.get(m.relative(-2))?;
+                    let b = 0;
+                    m.set(m.relative(-1), (a < b) as i64)?;
+                    m.jump(288);
+                }
+                288 => {
+                    if m.get(m.relative(-1))? == 0 {
+                        m.jump(294);
+                    } else {
+                        m.jump(291);
+                    }
+                }
+                291 => {
+                    let a = 0;
+                    m.jump(293);
+                    return Ok(Some(State::Output(a)));
+                }
+                293 => {
+                    return Ok(Some(State::Halted));
+                }
+                294 => {
+                    let a = 0;
+                    let b = m.get(m.relative(-2))?;
+                    m.set(m.relative(-2), m.add(a, b)?)?;
+                    m.jump(298);
+                }
+                298 => {
+                    let a = -3;
+                    m.jump(300);
+                    m.offset_relative_base(a);
+                }
+                300 => {
+                    m.jump(m.get(m.relative(0))?);
+                }
+                303 => {
+                    let a = 5;
+                    m.jump(305);
+                    m.offset_relative_base(a);
+                }
+                305 => {
+                    let a = m.get(m.relative(-3))?;
+                    let b = m.get(m.relative(-4))?;
+                    m.set(m.relative(-1), (a < b) as i64)?;
+                    m.jump(309);
+                }
+                309 => {
+                    if m.get(m.relative(-1))? == 0 {
+                        m.jump(346);
+                    } else {
+                        m.jump(312);
+                    }
+                }
+                312 => {
+                    let a = m.get(m.relative(-4))?;
+                    let b = m.get(m.relative(-3))?;
+                    m.set(m.relative(-4), m.add(a, b)?)?;
+                    m.jump(316);
+                }
+                316 => {
+                    let a = m.get(m.relative(-3))?;
+                    let b = -1;
+                    m.set(m.relative(-1), m.mul(a, b)?)?;
+                    m.jump(320);
+                }
+                320 => {
+                    let a = m.get(m.relative(-4))?;
+                    let b = m.get(m.relative(-1))?;
+                    m.set(m.relative(2), m.add(a, b)?)?;
+                    m.jump(324);
+                }
+                324 => {
+                    let a = m.get(m.relative(2))?;
+                    let b = -1;
+                    m.set(m.relative(-1), m.mul(a, b)?)?;
+                    m.jump(328);
+                }
+                328 => {
+                    let a = m.get(m.relative(-4))?;
+                    let b = m.get(m.relative(-1))?;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(332);
+                }
+                332 => {
+                    let a = 1;
+                    let b = m.get(m.relative(-2))?;
+                    m.set(m.relative(3), m.mul(a, b)?)?;
+                    m.jump(336);
+                }
+                336 => {
+                    let a = 343;
+                    let b = 1;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(340);
+                }
+                340 => {
+                    m.jump(303);
+                }
+                343 => {
+                    m.jump(415);
+                }
+                346 => {
+                    let a = m.get(m.relative(-2))?;
+                    let b = m.get(m.relative(-3))?;
+                    m.set(m.relative(-1), (a < b) as i64)?;
+                    m.jump(350);
+                }
+                350 => {
+                    if m.get(m.relative(-1))? == 0 {
+                        m.jump(387);
+                    } else {
+                        m.jump(353);
+                    }
+                }
+                353 => {
+                    let a = m.get(m.relative(-3))?;
+                    let b = m.get(m.relative(-2))?;
+                    m.set(m.relative(-3), m.add(a, b)?)?;
+                    m.jump(357);
+                }
+                357 => {
+                    let a = m.get(m.relative(-2))?;
+                    let b = -1;
+                    m.set(m.relative(-1), m.mul(a, b)?)?;
+                    m.jump(361);
+                }
+                361 => {
+                    let a = m.get(m.relative(-3))?;
+                    let b = m.get(m.relative(-1))?;
+                    m.set(m.relative(3), m.add(a, b)?)?;
+                    m.jump(365);
+                }
+                365 => {
+                    let a = m.get(m.relative(3))?;
+                    let b = -1;
+                    m.set(m.relative(-1), m.mul(a, b)?)?;
+                    m.jump(369);
+                }
+                369 => {
+                    let a = m.get(m.relative(-3))?;
+                    let b = m.get(m.relative(-1))?;
+                    m.set(m.relative(2), m.add(a, b)?)?;
+                    m.jump(373);
+                }
+                373 => {
+                    let a = m.get(m.relative(-4))?;
+                    let b = 0;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(377);
+                }
+                377 => {
+                    let a = 384;
+                    let b = 1;
+                    m.set(m.relative(0), m.mul(a, b)?)?;
+                    m.jump(381);
+                }
+                381 => {
+                    m.jump(303);
+                }
+                384 => {
+                    m.jump(415);
+                }
+                387 => {
+                    let a = m.get(m.relative(-4))?;
+                    let b = -1;
+                    m.set(m.relative(-4), m.mul(a, b)?)?;
+                    m.jump(391);
+                }
+                391 => {
+                    let a = m.get(m.relative(-4))?;
+                    let b = m.get(m.relative(-3))?;
+                    m.set(m.relative(-4), m.add(a, b)?)?;
+                    m.jump(395);
+                }
+                395 => {
+                    let a = m.get(m.relative(-3))?;
+                    let b = m.get(m.relative(-2))?;
+                    m.set(m.relative(-2), m.mul(a, b)?)?;
+                    m.jump(399);
+                }
+                399 => {
+                    let a = m.get(m.relative(-2))?;
+                    let b = m.get(m.relative(-4))?;
+                    m.set(m.relative(-4), m.mul(a, b)?)?;
+                    m.jump(403);
+                }
+                403 => {
+                    let a = m.get(m.relative(-3))?;
+                    let b = m.get(m.relative(-2))?;
+                    m.set(m.relative(-3), m.mul(a, b)?)?;
+                    m.jump(407);
+                }
+                407 => {
+                    let a = m.get(m.relative(-4))?;
+                    let b = -1;
+                    m.set(m.relative(-2), m.mul(a, b)?)?;
+                    m.jump(411);
+                }
+                411 => {
+                    let a = m.get(m.relative(-3))?;
+                    let b = m.get(m.relative(-2))?;
+                    m.set(m.relative(1), m.add(a, b)?)?;
+                    m.jump(415);
+                }
+                415 => {
+                    let a = 1;
+                    let b = m.get(m.relative(1))?;
+                    m.set(m.relative(-4), m.mul(a, b)?)?;
+                    m.jump(419);
+                }
+                419 => {
+                    let a = -5;
+                    m.jump(421);
+                    m.offset_relative_base(a);
+                }
+                421 => {
+                    m.jump(m.get(m.relative(0))?);
+                }
+                _ => break,
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,8 @@
+// programs translated ahead of time, regenerate with `intcode_transpile <day> <name>`
+#[rustfmt::skip]
+mod day_09;
+#[rustfmt::skip]
+mod day_19;
+
+pub use self::day_09::Day09;
+pub use self::day_19::Day19;
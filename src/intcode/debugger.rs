@@ -0,0 +1,442 @@
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+use super::ascii;
+use super::disasm::decode_line;
+use super::history::{History, Undone};
+use super::{Computer, ComputerError, Instruction, OpCode, State};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Event {
+    Stepped,
+    Breakpoint(i64),
+    Watchpoint { address: i64, old: i64, new: i64 },
+    Input,
+    Output(i64),
+    NeedsInput,
+    Halted,
+    Error(ComputerError),
+}
+
+// instructions the debugger can step back over
+const HISTORY_LIMIT: usize = 1_000_000;
+
+pub struct Debugger {
+    pub computer: Computer,
+    pub outputs: Vec<i64>,
+    breakpoints: BTreeSet<i64>,
+    watchpoints: BTreeSet<i64>,
+}
+
+impl Debugger {
+    // keeps a bounded history on the computer unless it already records one, and stops to ask
+    // for input instead of reading the default input
+    pub fn new(mut computer: Computer) -> Self {
+        computer.yield_on_empty = true;
+        if computer.history().is_none() {
+            computer.set_history(History::new().with_limit(HISTORY_LIMIT));
+        }
+        Debugger {
+            computer,
+            outputs: Vec::new(),
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+        }
+    }
+    pub fn add_breakpoint(&mut self, address: i64) {
+        self.breakpoints.insert(address);
+    }
+    pub fn remove_breakpoint(&mut self, address: i64) -> bool {
+        self.breakpoints.remove(&address)
+    }
+    pub fn breakpoints(&self) -> &BTreeSet<i64> {
+        &self.breakpoints
+    }
+    pub fn add_watchpoint(&mut self, address: i64) {
+        self.watchpoints.insert(address);
+    }
+    pub fn remove_watchpoint(&mut self, address: i64) -> bool {
+        self.watchpoints.remove(&address)
+    }
+    pub fn watchpoints(&self) -> &BTreeSet<i64> {
+        &self.watchpoints
+    }
+    pub fn memory(&self, range: Range<i64>) -> Vec<i64> {
+        range.map(|address| self.computer.peek(address)).collect()
+    }
+    fn reads_input(&self) -> bool {
+        Instruction::decode(self.computer.peek(self.computer.pos()), self.computer.pos())
+            .is_ok_and(|instruction| instruction.opcode() == OpCode::Input)
+    }
+    pub fn step(&mut self) -> Event {
+        let before = self.memory_at_watchpoints();
+        match self.computer.step() {
+            Err(error) => return Event::Error(error),
+            Ok(Some(State::Output(value))) => {
+                self.outputs.push(value);
+                return Event::Output(value);
+            }
+            Ok(Some(State::NeedsInput)) => return Event::NeedsInput,
+            Ok(Some(State::Halted)) => return Event::Halted,
+            Ok(None) => (),
+        }
+        before
+            .into_iter()
+            .zip(self.memory_at_watchpoints())
+            .find(|((_, old), (_, new))| old != new)
+            .map_or(Event::Stepped, |((address, old), (_, new))| {
+                Event::Watchpoint { address, old, new }
+            })
+    }
+    pub fn step_back(&mut self) -> Option<Undone> {
+        let undone = self.computer.step_back()?;
+        if undone.output.is_some() {
+            self.outputs.pop();
+        }
+        Some(undone)
+    }
+    // returns how many instructions were undone to get back before the last write to address
+    pub fn rewind_to_write(&mut self, address: i64) -> Option<usize> {
+        let steps = self.computer.history()?.last_write(address)?;
+        for _ in 0..steps {
+            self.step_back();
+        }
+        Some(steps)
+    }
+    // always executes at least one instruction, so continuing from a breakpoint makes progress
+    pub fn cont(&mut self) -> Event {
+        loop {
+            match self.step() {
+                Event::Stepped => (),
+                event => return event,
+            }
+            let pos = self.computer.pos();
+            if self.breakpoints.contains(&pos) {
+                return Event::Breakpoint(pos);
+            }
+            if self.reads_input() {
+                return Event::Input;
+            }
+        }
+    }
+    fn memory_at_watchpoints(&self) -> Vec<(i64, i64)> {
+        self.watchpoints
+            .iter()
+            .map(|&address| (address, self.computer.peek(address)))
+            .collect()
+    }
+    fn current_instruction(&self) -> String {
+        let pos = self.computer.pos();
+        let cells = self.memory(pos..pos.saturating_add(4));
+        match decode_line(&cells, 0) {
+            Some(line) => format!("{}: {}", pos, line.text()),
+            None => format!("{}: ?? {:?}", pos, cells),
+        }
+    }
+}
+
+// the most cells x prints at once
+const MAX_DUMP: i64 = 4096;
+
+const HELP: &str = "\
+commands:
+  s, step [n]        execute n instructions (default 1)
+  c, continue        run until a breakpoint, watchpoint, input, output or halt
+  bs, back [n]       undo the last n instructions (default 1)
+  rw, rewind <addr>  go back to just before the last write to addr
+  b, break <addr>    set a breakpoint
+  d, delete <addr>   remove a breakpoint
+  w, watch <addr>    stop when the value at addr changes
+  u, unwatch <addr>  remove a watchpoint
+  r, regs            show pos, relative base and pending input
+  x <addr> [len]     dump memory
+  i, input <n>...    queue integer input
+  a, ascii <text>    queue a line of ascii input
+  o, outputs         show outputs produced so far
+  q, quit            exit the debugger";
+
+fn describe(event: &Event) -> String {
+    match event {
+        Event::Stepped => "stepped".to_string(),
+        Event::Breakpoint(address) => format!("breakpoint at {}", address),
+        Event::Watchpoint { address, old, new } => {
+            format!("watchpoint {}: {} -> {}", address, old, new)
+        }
+        Event::Input => "about to read input".to_string(),
+        Event::Output(value) => format!("output {}", value),
+        Event::NeedsInput => "waiting for input".to_string(),
+        Event::Halted => "halted".to_string(),
+        Event::Error(error) => format!("error: {}", error),
+    }
+}
+
+fn parse_args(args: &[&str]) -> Result<Vec<i64>, String> {
+    args.iter()
+        .map(|arg| arg.parse().map_err(|_| format!("not a number: {}", arg)))
+        .collect()
+}
+
+fn dump<W: Write>(debugger: &Debugger, address: i64, len: i64, output: &mut W) -> io::Result<()> {
+    match address.checked_add(len) {
+        Some(end) if (0..=MAX_DUMP).contains(&len) => {
+            writeln!(output, "{:?}", debugger.memory(address..end))
+        }
+        _ => writeln!(
+            output,
+            "usage: x <addr> [len], with len at most {} and the cells inside memory",
+            MAX_DUMP
+        ),
+    }
+}
+
+fn execute<W: Write>(debugger: &mut Debugger, line: &str, output: &mut W) -> io::Result<bool> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (command, args) = match words.split_first() {
+        Some((command, args)) => (*command, args),
+        None => return Ok(true),
+    };
+    if command == "a" || command == "ascii" {
+        let text = line.trim_start()[command.len()..].trim();
+        debugger.computer.add_input(&ascii::encode(&[text]));
+        return Ok(true);
+    }
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(message) => {
+            writeln!(output, "{}", message)?;
+            return Ok(true);
+        }
+    };
+    match (command, args.as_slice()) {
+        ("s", []) | ("step", []) => {
+            writeln!(output, "{}", describe(&debugger.step()))?;
+        }
+        ("s", [n]) | ("step", [n]) => {
+            for _ in 0..*n {
+                let event = debugger.step();
+                if event != Event::Stepped {
+                    writeln!(output, "{}", describe(&event))?;
+                    break;
+                }
+            }
+        }
+        ("c", []) | ("continue", []) => {
+            writeln!(output, "{}", describe(&debugger.cont()))?;
+        }
+        ("bs", []) | ("back", []) => {
+            if debugger.step_back().is_none() {
+                writeln!(output, "nothing to undo")?;
+            }
+        }
+        ("bs", [n]) | ("back", [n]) => {
+            let undone = (0..*n)
+                .take_while(|_| debugger.step_back().is_some())
+                .count();
+            writeln!(output, "undid {} instructions", undone)?;
+        }
+        ("rw", [address]) | ("rewind", [address]) => match debugger.rewind_to_write(*address) {
+            Some(steps) => writeln!(output, "undid {} instructions", steps)?,
+            None => writeln!(output, "no recorded write to {}", address)?,
+        },
+        ("b", [address]) | ("break", [address]) => debugger.add_breakpoint(*address),
+        ("d", [address]) | ("delete", [address]) => {
+            if !debugger.remove_breakpoint(*address) {
+                writeln!(output, "no breakpoint at {}", address)?;
+            }
+        }
+        ("w", [address]) | ("watch", [address]) => debugger.add_watchpoint(*address),
+        ("u", [address]) | ("unwatch", [address]) => {
+            if !debugger.remove_watchpoint(*address) {
+                writeln!(output, "no watchpoint at {}", address)?;
+            }
+        }
+        ("r", []) | ("regs", []) => {
+            writeln!(
+                output,
+                "pos {} relative_base {} input {:?}",
+                debugger.computer.pos(),
+                debugger.computer.relative_base(),
+                debugger.computer.input_queue
+            )?;
+        }
+        ("x", [address]) => dump(debugger, *address, 1, output)?,
+        ("x", [address, len]) => dump(debugger, *address, *len, output)?,
+        ("i", values) | ("input", values) if !values.is_empty() => {
+            debugger.computer.add_input(values);
+        }
+        ("o", []) | ("outputs", []) => writeln!(output, "{:?}", debugger.outputs)?,
+        ("q", []) | ("quit", []) => return Ok(false),
+        ("h", []) | ("help", []) => writeln!(output, "{}", HELP)?,
+        _ => writeln!(output, "unknown command: {} (try help)", line.trim())?,
+    }
+    Ok(true)
+}
+
+pub fn repl<R: BufRead, W: Write>(
+    debugger: &mut Debugger,
+    input: R,
+    output: &mut W,
+) -> io::Result<()> {
+    writeln!(output, "{}", debugger.current_instruction())?;
+    for line in input.lines() {
+        if !execute(debugger, &line?, output)? {
+            break;
+        }
+        writeln!(output, "{}", debugger.current_instruction())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    fn program() -> Vec<i64> {
+        assemble(
+            "
+                in x
+            loop:
+                add x, #1, x
+                out x
+                lt x, #3, flag
+                jnz flag, #loop
+                hlt
+            x:    db 0
+            flag: db 0
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_breakpoints_and_watchpoints() {
+        let mut debugger = Debugger::new(Computer::new(&program(), &[0]));
+        assert_eq!(debugger.cont(), Event::Output(1));
+        debugger.add_breakpoint(2);
+        assert_eq!(debugger.cont(), Event::Breakpoint(2));
+        assert_eq!(debugger.computer.pos(), 2);
+        debugger.add_watchpoint(16);
+        assert_eq!(
+            debugger.cont(),
+            Event::Watchpoint {
+                address: 16,
+                old: 1,
+                new: 2
+            }
+        );
+        assert!(debugger.remove_breakpoint(2));
+        assert_eq!(debugger.cont(), Event::Output(2));
+        assert_eq!(debugger.memory(16..18), vec![2, 1]);
+        debugger.remove_watchpoint(16);
+        assert_eq!(debugger.cont(), Event::Output(3));
+        assert_eq!(debugger.cont(), Event::Halted);
+        assert_eq!(debugger.outputs, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_stops_before_input() {
+        // an empty input queue stops the program instead of reading the default input
+        let mut debugger = Debugger::new(Computer::new(&program(), &[]));
+        assert_eq!(debugger.step(), Event::NeedsInput);
+        assert_eq!(debugger.cont(), Event::NeedsInput);
+        assert_eq!(debugger.computer.pos(), 0);
+        debugger.computer.add_input(&[5]);
+        assert_eq!(debugger.step(), Event::Stepped);
+        assert_eq!(debugger.computer.peek(16), 5);
+        let mut output = Vec::new();
+        let mut waiting = Debugger::new(Computer::new(&program(), &[]));
+        repl(&mut waiting, "c\n".as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "0: in 16\nwaiting for input\n0: in 16\n"
+        );
+    }
+
+    #[test]
+    fn test_repl() {
+        let mut debugger = Debugger::new(Computer::new(&program(), &[]));
+        let commands = "i 1\nb 12\nc\nregs\nx 16 2\nfoo\nc\nc\nq\nc\n";
+        let mut output = Vec::new();
+        repl(&mut debugger, commands.as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "0: in 16\n",
+                "0: in 16\n",
+                "0: in 16\n",
+                "output 2\n",
+                "8: lt 16, #3, 17\n",
+                "pos 8 relative_base 0 input []\n",
+                "8: lt 16, #3, 17\n",
+                "[2, 0]\n",
+                "8: lt 16, #3, 17\n",
+                "unknown command: foo (try help)\n",
+                "8: lt 16, #3, 17\n",
+                "breakpoint at 12\n",
+                "12: jnz 17, #2\n",
+                "output 3\n",
+                "8: lt 16, #3, 17\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_dump_limits() {
+        let mut debugger = Debugger::new(Computer::new(&program(), &[]));
+        let commands = "x 9223372036854775807\nx 0 100000\nx 0 -1\nx 0 0\n";
+        let mut output = Vec::new();
+        repl(&mut debugger, commands.as_bytes(), &mut output).unwrap();
+        let usage = "usage: x <addr> [len], with len at most 4096 and the cells inside memory\n";
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "0: in 16\n{0}0: in 16\n{0}0: in 16\n{0}0: in 16\n[]\n0: in 16\n",
+                usage
+            )
+        );
+    }
+
+    #[test]
+    fn test_step_back() {
+        let mut debugger = Debugger::new(Computer::new(&program(), &[0]));
+        assert_eq!(debugger.cont(), Event::Output(1));
+        assert_eq!(debugger.cont(), Event::Output(2));
+        assert_eq!(debugger.rewind_to_write(16), Some(2));
+        assert_eq!(
+            (debugger.computer.pos(), debugger.computer.peek(16)),
+            (2, 1)
+        );
+        assert_eq!(debugger.outputs, vec![1]);
+        assert_eq!(
+            debugger.step_back().map(|undone| undone.address),
+            Some(15 - 3)
+        );
+        assert_eq!(debugger.rewind_to_write(99), None);
+        assert_eq!(debugger.cont(), Event::Output(2));
+        assert_eq!(debugger.outputs, vec![1, 2]);
+
+        let mut output = Vec::new();
+        let commands = "bs 100
+bs
+rw 16
+c
+";
+        repl(&mut debugger, commands.as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "8: lt 16, #3, 17\n",
+                "undid 7 instructions\n",
+                "0: in 16\n",
+                "nothing to undo\n",
+                "0: in 16\n",
+                "no recorded write to 16\n",
+                "0: in 16\n",
+                "output 1\n",
+                "8: lt 16, #3, 17\n",
+            )
+        );
+    }
+}
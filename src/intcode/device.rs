@@ -0,0 +1,193 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{Receiver, Sender};
+
+use super::{Computer, Memory};
+
+// None means no input is available, which suspends the computer with State::NeedsInput
+pub trait InputDevice {
+    fn read(&mut self) -> Option<i64>;
+}
+
+pub trait OutputDevice {
+    fn write(&mut self, value: i64);
+}
+
+impl InputDevice for VecDeque<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+}
+
+impl OutputDevice for Vec<i64> {
+    fn write(&mut self, value: i64) {
+        self.push(value);
+    }
+}
+
+impl<F: FnMut() -> Option<i64>> InputDevice for F {
+    fn read(&mut self) -> Option<i64> {
+        self()
+    }
+}
+
+impl<F: FnMut(i64)> OutputDevice for F {
+    fn write(&mut self, value: i64) {
+        self(value)
+    }
+}
+
+// blocks until a value arrives, a disconnected sender reads as no input
+impl InputDevice for Receiver<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.recv().ok()
+    }
+}
+
+// a receiver that has gone away just drops the value
+impl OutputDevice for Sender<i64> {
+    fn write(&mut self, value: i64) {
+        self.send(value).ok();
+    }
+}
+
+// reading from another computer runs it until its next output
+impl<M: Memory> InputDevice for Computer<M> {
+    fn read(&mut self) -> Option<i64> {
+        self.next()
+    }
+}
+
+impl<M: Memory> OutputDevice for Computer<M> {
+    fn write(&mut self, value: i64) {
+        self.add_input(&[value]);
+    }
+}
+
+pub(super) struct Split<'a, I: ?Sized, O: ?Sized> {
+    pub(super) input: &'a mut I,
+    pub(super) output: &'a mut O,
+}
+
+impl<'a, I: InputDevice + ?Sized, O: ?Sized> InputDevice for Split<'a, I, O> {
+    fn read(&mut self) -> Option<i64> {
+        self.input.read()
+    }
+}
+
+impl<'a, I: ?Sized, O: OutputDevice + ?Sized> OutputDevice for Split<'a, I, O> {
+    fn write(&mut self, value: i64) {
+        self.output.write(value)
+    }
+}
+
+// how an executing instruction talks to the outside world. the computer's own queue is the
+// default, attached devices are only borrowed for the length of a run
+pub(super) trait Io<M: Memory> {
+    // whether input comes off the computer's own queue
+    const QUEUED: bool = false;
+
+    fn input(&mut self, computer: &mut Computer<M>) -> Option<i64>;
+    // returns whether the computer should stop and hand the value back as State::Output
+    fn output(&mut self, value: i64) -> bool;
+}
+
+pub(super) struct Queue;
+
+impl<M: Memory> Io<M> for Queue {
+    const QUEUED: bool = true;
+
+    fn input(&mut self, computer: &mut Computer<M>) -> Option<i64> {
+        if computer.yield_on_empty && computer.input_queue.is_empty() {
+            return None;
+        }
+        Some(
+            computer
+                .input_queue
+                .pop_front()
+                .unwrap_or(computer.default_input),
+        )
+    }
+    fn output(&mut self, _: i64) -> bool {
+        true
+    }
+}
+
+pub(super) struct Attached<'a, D: ?Sized>(pub(super) &'a mut D);
+
+impl<'a, M: Memory, D: InputDevice + OutputDevice + ?Sized> Io<M> for Attached<'a, D> {
+    fn input(&mut self, _: &mut Computer<M>) -> Option<i64> {
+        self.0.read()
+    }
+    fn output(&mut self, value: i64) -> bool {
+        self.0.write(value);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+    use crate::intcode::State;
+    use std::sync::mpsc::channel;
+    use std::thread;
+
+    // doubles every input until it reads a zero
+    fn program() -> Vec<i64> {
+        assemble(
+            "
+            loop:
+                in x
+                jz x, #done
+                mul x, #2, x
+                out x
+                jnz #1, #loop
+            done:
+                hlt
+            x: db 0
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_callbacks() {
+        let mut computer = Computer::new(&program(), &[]);
+        let mut input = vec![1, 2, 3].into_iter();
+        let mut output = Vec::new();
+        let state = computer.run_devices(&mut || input.next(), &mut |x| output.push(x));
+        assert_eq!(state, Ok(State::NeedsInput));
+        assert_eq!(output, vec![2, 4, 6]);
+
+        let mut input: VecDeque<i64> = vec![5, 0].into();
+        let state = computer.run_devices(&mut input, &mut output);
+        assert_eq!(state, Ok(State::Halted));
+        assert_eq!(output, vec![2, 4, 6, 10]);
+    }
+
+    #[test]
+    fn test_chained_computers() {
+        let mut first = Computer::new(&program(), &[1, 3, 0]);
+        let mut second = Computer::new(&program(), &[]);
+        let mut output = Vec::new();
+        assert_eq!(
+            second.run_devices(&mut first, &mut output),
+            Ok(State::NeedsInput)
+        );
+        assert_eq!(output, vec![4, 12]);
+    }
+
+    #[test]
+    fn test_channels() {
+        let (to_computer, input) = channel();
+        let (mut output, from_computer) = channel();
+        let handle = thread::spawn(move || {
+            let mut input = input;
+            Computer::new(&program(), &[]).run_devices(&mut input, &mut output)
+        });
+        to_computer.send(21).unwrap();
+        assert_eq!(from_computer.recv(), Ok(42));
+        to_computer.send(0).unwrap();
+        assert_eq!(handle.join().unwrap(), Ok(State::Halted));
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use super::{Instruction, OpCode, ParameterMode};
+
+const DATA_WIDTH: usize = 8;
+const TEXT_WIDTH: usize = 16;
+const MIN_TEXT: usize = 4;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Operand {
+    pub mode: ParameterMode,
+    pub value: i64,
+    // immediate operand pointing at a labelled code address
+    pub label: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Line {
+    Code {
+        address: i64,
+        opcode: OpCode,
+        operands: Vec<Operand>,
+    },
+    Data {
+        address: i64,
+        values: Vec<i64>,
+    },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Disassembly {
+    pub lines: Vec<Line>,
+    pub labels: BTreeSet<i64>,
+}
+
+impl Line {
+    pub fn address(&self) -> i64 {
+        match self {
+            Line::Code { address, .. } | Line::Data { address, .. } => *address,
+        }
+    }
+}
+
+pub fn label(address: i64) -> String {
+    format!("L{}", address)
+}
+
+fn is_printable(x: i64) -> bool {
+    x == 10 || (32..127).contains(&x)
+}
+
+fn as_text(values: &[i64]) -> Option<String> {
+    if values.len() >= MIN_TEXT && values.iter().cloned().all(is_printable) {
+        Some(values.iter().map(|&x| x as u8 as char).collect())
+    } else {
+        None
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            ParameterMode::Position => write!(f, "{}", self.value),
+            ParameterMode::Immediate if self.label => write!(f, "#{}", label(self.value)),
+            ParameterMode::Immediate => write!(f, "#{}", self.value),
+            ParameterMode::Relative if self.value < 0 => write!(f, "rb-{}", -self.value),
+            ParameterMode::Relative => write!(f, "rb+{}", self.value),
+        }
+    }
+}
+
+impl Line {
+    // the assembly text without the address comment
+    pub fn text(&self) -> String {
+        match self {
+            Line::Code {
+                opcode, operands, ..
+            } => {
+                let operands: Vec<String> = operands.iter().map(Operand::to_string).collect();
+                format!("{} {}", opcode.mnemonic(), operands.join(", "))
+                    .trim_end()
+                    .to_string()
+            }
+            Line::Data { values, .. } => {
+                let values: Vec<String> = values.iter().map(i64::to_string).collect();
+                format!("db {}", values.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let comment = match self {
+            Line::Data { address, values } if as_text(values).is_some() => {
+                format!("{} {:?}", address, as_text(values).unwrap())
+            }
+            line => line.address().to_string(),
+        };
+        write!(f, "    {:<40} ; {}", self.text(), comment)
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in self.lines.iter() {
+            if let Line::Code { address, .. } = line {
+                if self.labels.contains(address) {
+                    writeln!(f, "{}:", label(*address))?;
+                }
+            }
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+// decodes the instruction at address, rejecting anything a real program could not have written
+pub(super) fn decode_at(program: &[i64], address: usize) -> Option<(OpCode, Vec<Operand>)> {
+    let instruction = Instruction::decode(program[address], address as i64).ok()?;
+    let opcode = instruction.opcode();
+    let count = opcode.parameter_count();
+    let modes = instruction.modes();
+    if address + count >= program.len()
+        || modes[count..].iter().any(|&m| m != ParameterMode::Position)
+        || opcode
+            .written_parameter()
+            .is_some_and(|i| modes[i] == ParameterMode::Immediate)
+    {
+        return None;
+    }
+    let operands = (0..count)
+        .map(|i| Operand {
+            mode: modes[i],
+            value: program[address + 1 + i],
+            label: false,
+        })
+        .collect();
+    Some((opcode, operands))
+}
+
+// decodes a single instruction without looking at the rest of the program
+pub fn decode_line(program: &[i64], address: usize) -> Option<Line> {
+    if address >= program.len() {
+        return None;
+    }
+    decode_at(program, address).map(|(opcode, operands)| Line::Code {
+        address: address as i64,
+        opcode,
+        operands,
+    })
+}
+
+pub(super) fn is_jump(opcode: OpCode) -> bool {
+    opcode == OpCode::JumpIfTrue || opcode == OpCode::JumpIfFalse
+}
+
+// recognizes `add #0, #ret, ...` / `mul #1, #ret, ...` directly followed by a jump to a
+// subroutine, where ret is the address just after that jump
+pub(super) fn return_address(
+    program: &[i64],
+    end: usize,
+    opcode: OpCode,
+    operands: &[Operand],
+) -> Option<usize> {
+    if (opcode != OpCode::Add && opcode != OpCode::Multiply)
+        || operands[..2]
+            .iter()
+            .any(|operand| operand.mode != ParameterMode::Immediate)
+    {
+        return None;
+    }
+    // a sum that overflows can't be an address anyway
+    let value = match opcode {
+        OpCode::Add => operands[0].value.checked_add(operands[1].value)?,
+        _ => operands[0].value.checked_mul(operands[1].value)?,
+    };
+    match decode_at(program, end) {
+        Some((next, _)) if is_jump(next) && value == end as i64 + 3 => Some(value as usize),
+        _ => None,
+    }
+}
+
+fn text_run(program: &[i64], start: usize, end: usize) -> usize {
+    program[start..end]
+        .iter()
+        .take_while(|&&x| is_printable(x))
+        .count()
+}
+
+// splits an unreachable region into db lines, giving runs of ascii text their own lines
+fn data_lines(program: &[i64], start: usize, end: usize) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut address = start;
+    while address < end {
+        let run = text_run(program, address, end);
+        let len = if run >= MIN_TEXT {
+            run.min(TEXT_WIDTH)
+        } else {
+            (address..end)
+                .take(DATA_WIDTH)
+                .skip(1)
+                .find(|&i| text_run(program, i, end) >= MIN_TEXT)
+                .map_or((end - address).min(DATA_WIDTH), |i| i - address)
+        };
+        lines.push(Line::Data {
+            address: address as i64,
+            values: program[address..address + len].to_vec(),
+        });
+        address += len;
+    }
+    lines
+}
+
+pub fn disassemble(program: &[i64]) -> Disassembly {
+    let mut code: BTreeMap<usize, (OpCode, Vec<Operand>)> = BTreeMap::new();
+    let mut claimed = vec![false; program.len()];
+    let mut labels = BTreeSet::new();
+    let mut pending = vec![0];
+    while let Some(address) = pending.pop() {
+        if address >= program.len() || claimed[address] {
+            continue;
+        }
+        let (opcode, mut operands) = match decode_at(program, address) {
+            Some(decoded) => decoded,
+            None => continue,
+        };
+        let end = address + 1 + operands.len();
+        if claimed[address..end].iter().any(|&x| x) {
+            continue;
+        }
+        claimed[address..end].iter_mut().for_each(|x| *x = true);
+
+        let mut targets = Vec::new();
+        let falls_through = match opcode {
+            OpCode::Stop => false,
+            OpCode::JumpIfTrue | OpCode::JumpIfFalse => {
+                let condition = operands[0];
+                let jumps = condition.mode != ParameterMode::Immediate
+                    || (condition.value != 0) == (opcode == OpCode::JumpIfTrue);
+                if jumps && operands[1].mode == ParameterMode::Immediate {
+                    targets.push((1, operands[1].value));
+                }
+                condition.mode != ParameterMode::Immediate
+                    || (condition.value == 0) == (opcode == OpCode::JumpIfTrue)
+            }
+            OpCode::Add | OpCode::Multiply => {
+                if let Some(ret) = return_address(program, end, opcode, &operands) {
+                    let index = if operands[0].value == ret as i64 {
+                        0
+                    } else {
+                        1
+                    };
+                    targets.push((index, ret as i64));
+                }
+                true
+            }
+            _ => true,
+        };
+        for (index, target) in targets {
+            if (0..program.len() as i64).contains(&target) {
+                operands[index].label = true;
+                labels.insert(target);
+                pending.push(target as usize);
+            }
+        }
+        if falls_through {
+            pending.push(end);
+        }
+        code.insert(address, (opcode, operands));
+    }
+
+    // jumps into the middle of another instruction don't get a label
+    labels.retain(|&target| code.contains_key(&(target as usize)));
+    let mut lines = Vec::new();
+    let mut address = 0;
+    while address < program.len() {
+        if let Some((opcode, mut operands)) = code.remove(&address) {
+            for operand in operands.iter_mut() {
+                operand.label = operand.label && labels.contains(&operand.value);
+            }
+            let next = address + 1 + operands.len();
+            lines.push(Line::Code {
+                address: address as i64,
+                opcode,
+                operands,
+            });
+            address = next;
+        } else {
+            let end = (address..program.len())
+                .find(|&i| claimed[i])
+                .unwrap_or(program.len());
+            lines.extend(data_lines(program, address, end));
+            address = end;
+        }
+    }
+    Disassembly { lines, labels }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::analysis::analyze;
+    use crate::intcode::transpile::transpile;
+    use crate::intcode::Computer;
+
+    #[test]
+    fn test_disassemble() {
+        let program: Vec<i64> = vec![
+            1105, 1, 7, 72, 105, 33, 33, 21101, 0, 14, 0, 1105, 1, 16, 204, -1, 99,
+        ];
+        let disassembly = disassemble(&program);
+        assert_eq!(
+            disassembly.to_string(),
+            concat!(
+                "    jnz #1, #L7                              ; 0\n",
+                "    db 72, 105, 33, 33                       ; 3 \"Hi!!\"\n",
+                "L7:\n",
+                "    add #0, #L14, rb+0                       ; 7\n",
+                "    jnz #1, #L16                             ; 11\n",
+                "L14:\n",
+                "    out rb-1                                 ; 14\n",
+                "L16:\n",
+                "    hlt                                      ; 16\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_unreachable_code_is_data() {
+        let program: Vec<i64> = vec![99, 1, 2, 3, 104];
+        let disassembly = disassemble(&program);
+        assert_eq!(
+            disassembly.lines,
+            vec![
+                Line::Code {
+                    address: 0,
+                    opcode: OpCode::Stop,
+                    operands: vec![],
+                },
+                Line::Data {
+                    address: 1,
+                    values: vec![1, 2, 3, 104],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overflowing_return_address() {
+        // the call check adds the operands, which mustn't panic when they overflow
+        let program: Vec<i64> = vec![1101, i64::MAX, 1, 7, 1105, 1, 8, 0, 99];
+        let disassembly = disassemble(&program);
+        assert_eq!(
+            disassembly.to_string(),
+            concat!(
+                "    add #9223372036854775807, #1, 7          ; 0\n",
+                "    jnz #1, #L8                              ; 4\n",
+                "    db 0                                     ; 7\n",
+                "L8:\n",
+                "    hlt                                      ; 8\n",
+            )
+        );
+        analyze(&program);
+        transpile(&program, "Overflow");
+    }
+
+    #[test]
+    fn test_disassemble_springdroid() {
+        let disassembly = disassemble(&Computer::load_data(21).unwrap());
+        let text: String = disassembly
+            .lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Data { values, .. } => as_text(values),
+                Line::Code { .. } => None,
+            })
+            .collect();
+        assert!(text.contains("Input instructions:"));
+    }
+}
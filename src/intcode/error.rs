@@ -0,0 +1,82 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ComputerError {
+    UnknownOpCode {
+        opcode: i64,
+        address: i64,
+    },
+    BadParameterMode {
+        mode: i64,
+        address: i64,
+    },
+    ImmediateWrite {
+        address: i64,
+    },
+    NegativeAddress {
+        address: i64,
+    },
+    Parse {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    Trace(String),
+    BudgetExceeded {
+        address: i64,
+    },
+    Overflow {
+        address: i64,
+    },
+    AddressOutOfRange {
+        address: i64,
+    },
+    // every computer in a circuit is waiting for input from the others
+    Deadlock,
+}
+
+impl fmt::Display for ComputerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ComputerError::UnknownOpCode { opcode, address } => {
+                write!(f, "unknown opcode {} at address {}", opcode, address)
+            }
+            ComputerError::BadParameterMode { mode, address } => {
+                write!(f, "bad parameter mode {} at address {}", mode, address)
+            }
+            ComputerError::ImmediateWrite { address } => write!(
+                f,
+                "instruction at address {} writes to an immediate mode parameter",
+                address
+            ),
+            ComputerError::NegativeAddress { address } => {
+                write!(f, "attempted to access negative address {}", address)
+            }
+            ComputerError::Parse {
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "could not parse {:?} as an integer at line {}, column {}",
+                token, line, column
+            ),
+            ComputerError::Trace(message) => write!(f, "could not write trace: {}", message),
+            ComputerError::BudgetExceeded { address } => {
+                write!(f, "ran out of instructions at address {}", address)
+            }
+            ComputerError::Overflow { address } => {
+                write!(f, "arithmetic overflow at address {}", address)
+            }
+            ComputerError::AddressOutOfRange { address } => write!(
+                f,
+                "instruction at address {} uses a value too large to be an address",
+                address
+            ),
+            ComputerError::Deadlock => write!(f, "every computer is waiting for input"),
+        }
+    }
+}
+
+impl Error for ComputerError {}
@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+
+// an instruction that was undone, with the input it consumed and the output it produced
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Undone {
+    pub address: i64,
+    pub input: Option<i64>,
+    pub output: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct Entry {
+    pub(super) address: i64,
+    pub(super) relative_base: i64,
+    // the cell the instruction overwrote and its old value
+    pub(super) write: Option<(i64, i64)>,
+    // whether the input came off the input queue, and so goes back on it when undone
+    pub(super) input: Option<(i64, bool)>,
+    pub(super) output: Option<i64>,
+}
+
+impl Entry {
+    pub(super) fn undone(&self) -> Undone {
+        Undone {
+            address: self.address,
+            input: self.input.map(|(value, _)| value),
+            output: self.output,
+        }
+    }
+}
+
+// the undo log a computer keeps of every instruction it executes, so it can run backwards
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    entries: VecDeque<Entry>,
+    limit: Option<usize>,
+    pub(super) pending: Entry,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History::default()
+    }
+    // forgets the oldest instructions once more than this many have been recorded
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+    // the number of instructions that can be undone
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    // how many instructions back the last write to address was, if it is still recorded
+    pub fn last_write(&self, address: i64) -> Option<usize> {
+        self.entries
+            .iter()
+            .rev()
+            .position(|entry| entry.write.is_some_and(|(written, _)| written == address))
+            .map(|i| i + 1)
+    }
+    pub(super) fn begin(&mut self, address: i64, relative_base: i64) {
+        self.pending = Entry {
+            address,
+            relative_base,
+            ..Default::default()
+        };
+    }
+    pub(super) fn commit(&mut self) {
+        if self.limit == Some(self.entries.len()) {
+            self.entries.pop_front();
+        }
+        if self.limit != Some(0) {
+            self.entries.push_back(self.pending);
+        }
+    }
+    pub(super) fn pop(&mut self) -> Option<Entry> {
+        self.entries.pop_back()
+    }
+    pub(super) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+    use crate::intcode::{Computer, State};
+
+    // everything that should come back when the computer is rewound
+    fn state(computer: &Computer) -> (i64, i64, Vec<i64>, Vec<i64>) {
+        (
+            computer.pos(),
+            computer.relative_base(),
+            computer.input_queue.iter().cloned().collect(),
+            (0..2000).map(|address| computer.peek(address)).collect(),
+        )
+    }
+
+    #[test]
+    fn test_step_back() {
+        let program = Computer::load_data(9).unwrap();
+        let mut computer = Computer::new(&program, &[1]);
+        computer.set_history(History::new());
+        let mut states = Vec::new();
+        let mut outputs = Vec::new();
+        for _ in 0..5000 {
+            states.push(state(&computer));
+            if let Some(State::Output(value)) = computer.step().unwrap() {
+                outputs.push(value);
+            }
+        }
+        assert_eq!(computer.history().unwrap().len(), 5000);
+        while let Some(state) = states.pop() {
+            let undone = computer.step_back().unwrap();
+            assert_eq!(undone.address, state.0);
+            if let Some(value) = undone.output {
+                assert_eq!(outputs.pop(), Some(value));
+            }
+            assert_eq!(super::tests::state(&computer), state);
+        }
+        assert_eq!(computer.step_back(), None);
+        // and forwards again to the same answer
+        let expected: Vec<i64> = Computer::new(&program, &[1]).collect();
+        assert_eq!(computer.collect::<Vec<i64>>(), expected);
+    }
+
+    #[test]
+    fn test_rewind_to_write() {
+        let program = assemble(
+            "
+                in x
+                add x, #1, y
+                add y, #1, x
+                out x
+                hlt
+            x: db 0
+            y: db 0
+            ",
+        )
+        .unwrap();
+        let mut computer = Computer::new(&program, &[5]);
+        computer.set_history(History::new());
+        assert_eq!(computer.run(), Ok(State::Output(7)));
+        let x = computer.pos() + 1;
+        let undone = computer.rewind_to_write(x).unwrap();
+        assert_eq!(undone.len(), 2);
+        assert_eq!((computer.pos(), computer.peek(x)), (6, 5));
+        assert_eq!(computer.rewind_to_write(x).unwrap().len(), 2);
+        // the input went back on the queue and the program runs the same way again
+        assert_eq!((computer.pos(), computer.peek(x)), (0, 0));
+        assert_eq!(computer.input_queue, vec![5]);
+        assert_eq!(computer.rewind_to_write(x), None);
+        assert_eq!(computer.run(), Ok(State::Output(7)));
+    }
+
+    #[test]
+    fn test_device_input_is_handed_back() {
+        let mut computer = Computer::new(&vec![3, 7, 4, 7, 99, 0, 0, 0], &[]);
+        computer.set_history(History::new().with_limit(2));
+        let mut input = vec![42].into_iter();
+        let mut output = Vec::new();
+        let state = computer.run_devices(&mut || input.next(), &mut output);
+        assert_eq!(state, Ok(State::Halted));
+        assert_eq!(output, vec![42]);
+        // the limit only kept the output and the halt
+        assert_eq!(
+            computer.rewind(3),
+            vec![
+                Undone {
+                    address: 4,
+                    input: None,
+                    output: None
+                },
+                Undone {
+                    address: 2,
+                    input: None,
+                    output: Some(42)
+                },
+            ]
+        );
+        computer.reset();
+        computer.set_history(History::new());
+        computer.run_devices(&mut || Some(-1), &mut output).unwrap();
+        assert_eq!(computer.rewind_to_write(7).unwrap()[2].input, Some(-1));
+        assert!(computer.input_queue.is_empty());
+    }
+}
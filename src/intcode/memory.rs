@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+// how far past the end of dense memory a write may land before it goes to the sparse map
+const MAX_GROWTH: i64 = 1 << 16;
+
+// addresses passed in are never negative, the computer checks that before touching memory
+pub trait Memory: Clone + Debug + Default {
+    fn from_program(data: &[i64]) -> Self;
+    fn get(&self, address: i64) -> i64;
+    fn set(&mut self, address: i64, value: i64);
+    // every cell that has been written, in address order
+    fn cells(&self) -> Vec<(i64, i64)>;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SparseMemory(HashMap<i64, i64>);
+
+impl Memory for SparseMemory {
+    fn from_program(data: &[i64]) -> Self {
+        SparseMemory(
+            data.iter()
+                .enumerate()
+                .map(|(i, &x)| (i as i64, x))
+                .collect(),
+        )
+    }
+    fn get(&self, address: i64) -> i64 {
+        *self.0.get(&address).unwrap_or(&0)
+    }
+    fn set(&mut self, address: i64, value: i64) {
+        self.0.insert(address, value);
+    }
+    fn cells(&self) -> Vec<(i64, i64)> {
+        let mut cells: Vec<(i64, i64)> = self.0.iter().map(|(&a, &x)| (a, x)).collect();
+        cells.sort();
+        cells
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DenseMemory {
+    cells: Vec<i64>,
+    far: HashMap<i64, i64>,
+}
+
+impl Memory for DenseMemory {
+    fn from_program(data: &[i64]) -> Self {
+        DenseMemory {
+            cells: data.to_vec(),
+            far: HashMap::new(),
+        }
+    }
+    fn get(&self, address: i64) -> i64 {
+        match self.cells.get(address as usize) {
+            Some(&x) => x,
+            None if self.far.is_empty() => 0,
+            None => *self.far.get(&address).unwrap_or(&0),
+        }
+    }
+    fn set(&mut self, address: i64, value: i64) {
+        let len = self.cells.len() as i64;
+        if address < len {
+            self.cells[address as usize] = value;
+        } else if address < len + MAX_GROWTH {
+            self.cells.resize(address as usize + 1, 0);
+            // pull in far cells that the dense region has now grown over
+            let covered: Vec<i64> = self
+                .far
+                .keys()
+                .filter(|&&a| a <= address)
+                .cloned()
+                .collect();
+            for a in covered {
+                self.cells[a as usize] = self.far.remove(&a).unwrap();
+            }
+            self.cells[address as usize] = value;
+        } else {
+            self.far.insert(address, value);
+        }
+    }
+    fn cells(&self) -> Vec<(i64, i64)> {
+        let mut cells: Vec<(i64, i64)> = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| (i as i64, x))
+            .chain(self.far.iter().map(|(&a, &x)| (a, x)))
+            .collect();
+        cells.sort();
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise<M: Memory>() -> M {
+        let mut memory = M::from_program(&[1, 2, 3]);
+        memory.set(1, 20);
+        memory.set(5, 6);
+        memory.set(1 << 40, 7);
+        memory
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let (dense, sparse) = (exercise::<DenseMemory>(), exercise::<SparseMemory>());
+        for &address in [0, 1, 2, 3, 4, 5, 6, 1 << 40, (1 << 40) + 1].iter() {
+            assert_eq!(dense.get(address), sparse.get(address), "{}", address);
+        }
+        assert_eq!(
+            dense.cells(),
+            vec![
+                (0, 1),
+                (1, 20),
+                (2, 3),
+                (3, 0),
+                (4, 0),
+                (5, 6),
+                (1 << 40, 7)
+            ]
+        );
+        assert_eq!(
+            sparse.cells(),
+            vec![(0, 1), (1, 20), (2, 3), (5, 6), (1 << 40, 7)]
+        );
+    }
+
+    #[test]
+    fn test_far_writes_stay_sparse() {
+        let mut memory = DenseMemory::from_program(&[0; 10]);
+        memory.set(1 << 20, 1);
+        assert_eq!(memory.cells.len(), 10);
+        memory.set(1000, 2);
+        assert_eq!(memory.cells.len(), 1001);
+        assert_eq!(memory.get(1 << 20), 1);
+        for address in (1000..1 << 20).step_by(1 << 15) {
+            memory.set(address, 3);
+        }
+        memory.set((1 << 20) + 1, 4);
+        assert!(memory.far.is_empty());
+        assert_eq!(memory.get(1 << 20), 1);
+    }
+}
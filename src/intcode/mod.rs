@@ -1,6 +1,45 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::str::FromStr;
 
-#[derive(Debug, Eq, PartialEq)]
+pub mod analysis;
+pub mod ascii;
+pub mod asm;
+mod cache;
+pub mod capture;
+pub mod circuit;
+pub mod compiled;
+pub mod debugger;
+mod device;
+pub mod disasm;
+mod error;
+pub mod history;
+pub mod memory;
+pub mod network;
+pub mod parallel;
+pub mod patch;
+pub mod play;
+pub mod profile;
+pub mod snapshot;
+mod stats;
+pub mod symbolic;
+pub mod trace;
+pub mod transpile;
+pub mod wide;
+
+use self::cache::DecodeCache;
+use self::device::{Attached, Io, Queue, Split};
+pub use self::device::{InputDevice, OutputDevice};
+pub use self::error::ComputerError;
+use self::history::{History, Undone};
+pub use self::memory::{DenseMemory, Memory, SparseMemory};
+use self::patch::Patch;
+use self::profile::Profiler;
+use self::snapshot::Snapshot;
+pub use self::stats::Stats;
+use self::trace::Tracer;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum OpCode {
     Add,
     Multiply,
@@ -14,237 +53,699 @@ pub enum OpCode {
     Stop,
 }
 
-impl From<&str> for OpCode {
-    fn from(x: &str) -> Self {
+impl TryFrom<&str> for OpCode {
+    type Error = i64;
+
+    fn try_from(x: &str) -> Result<Self, i64> {
         match x {
-            "01" => OpCode::Add,
-            "02" => OpCode::Multiply,
-            "03" => OpCode::Input,
-            "04" => OpCode::Output,
-            "05" => OpCode::JumpIfTrue,
-            "06" => OpCode::JumpIfFalse,
-            "07" => OpCode::LessThan,
-            "08" => OpCode::Equals,
-            "09" => OpCode::RelativeBaseOffset,
-            "99" => OpCode::Stop,
-            z => panic!("opcode failure! {}", z),
+            "01" => Ok(OpCode::Add),
+            "02" => Ok(OpCode::Multiply),
+            "03" => Ok(OpCode::Input),
+            "04" => Ok(OpCode::Output),
+            "05" => Ok(OpCode::JumpIfTrue),
+            "06" => Ok(OpCode::JumpIfFalse),
+            "07" => Ok(OpCode::LessThan),
+            "08" => Ok(OpCode::Equals),
+            "09" => Ok(OpCode::RelativeBaseOffset),
+            "99" => Ok(OpCode::Stop),
+            z => Err(z.parse().unwrap_or(-1)),
+        }
+    }
+}
+
+impl OpCode {
+    pub fn from_code(code: i64) -> Option<OpCode> {
+        match code {
+            1 => Some(OpCode::Add),
+            2 => Some(OpCode::Multiply),
+            3 => Some(OpCode::Input),
+            4 => Some(OpCode::Output),
+            5 => Some(OpCode::JumpIfTrue),
+            6 => Some(OpCode::JumpIfFalse),
+            7 => Some(OpCode::LessThan),
+            8 => Some(OpCode::Equals),
+            9 => Some(OpCode::RelativeBaseOffset),
+            99 => Some(OpCode::Stop),
+            _ => None,
+        }
+    }
+    pub fn code(self) -> i64 {
+        match self {
+            OpCode::Add => 1,
+            OpCode::Multiply => 2,
+            OpCode::Input => 3,
+            OpCode::Output => 4,
+            OpCode::JumpIfTrue => 5,
+            OpCode::JumpIfFalse => 6,
+            OpCode::LessThan => 7,
+            OpCode::Equals => 8,
+            OpCode::RelativeBaseOffset => 9,
+            OpCode::Stop => 99,
+        }
+    }
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            OpCode::Add => "add",
+            OpCode::Multiply => "mul",
+            OpCode::Input => "in",
+            OpCode::Output => "out",
+            OpCode::JumpIfTrue => "jnz",
+            OpCode::JumpIfFalse => "jz",
+            OpCode::LessThan => "lt",
+            OpCode::Equals => "eq",
+            OpCode::RelativeBaseOffset => "arb",
+            OpCode::Stop => "hlt",
+        }
+    }
+    pub fn from_mnemonic(mnemonic: &str) -> Option<OpCode> {
+        match mnemonic {
+            "add" => Some(OpCode::Add),
+            "mul" => Some(OpCode::Multiply),
+            "in" => Some(OpCode::Input),
+            "out" => Some(OpCode::Output),
+            "jnz" => Some(OpCode::JumpIfTrue),
+            "jz" => Some(OpCode::JumpIfFalse),
+            "lt" => Some(OpCode::LessThan),
+            "eq" => Some(OpCode::Equals),
+            "arb" => Some(OpCode::RelativeBaseOffset),
+            "hlt" => Some(OpCode::Stop),
+            _ => None,
+        }
+    }
+    pub fn parameter_count(self) -> usize {
+        match self {
+            OpCode::Add | OpCode::Multiply | OpCode::LessThan | OpCode::Equals => 3,
+            OpCode::JumpIfTrue | OpCode::JumpIfFalse => 2,
+            OpCode::Input | OpCode::Output | OpCode::RelativeBaseOffset => 1,
+            OpCode::Stop => 0,
+        }
+    }
+    // index of the parameter the instruction writes to, if any
+    pub fn written_parameter(self) -> Option<usize> {
+        match self {
+            OpCode::Add | OpCode::Multiply | OpCode::LessThan | OpCode::Equals => Some(2),
+            OpCode::Input => Some(0),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum ParameterMode {
     Position,
     Immediate,
     Relative,
 }
 
-impl From<char> for ParameterMode {
-    fn from(x: char) -> Self {
+impl TryFrom<char> for ParameterMode {
+    type Error = i64;
+
+    fn try_from(x: char) -> Result<Self, i64> {
         match x {
-            '0' => ParameterMode::Position,
-            '1' => ParameterMode::Immediate,
-            '2' => ParameterMode::Relative,
-            _ => panic!("unrecognized parameter mode {}", x),
+            '0' => Ok(ParameterMode::Position),
+            '1' => Ok(ParameterMode::Immediate),
+            '2' => Ok(ParameterMode::Relative),
+            _ => Err(x.to_digit(10).map_or(-1, i64::from)),
         }
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+impl ParameterMode {
+    pub fn from_digit(digit: i64) -> Option<ParameterMode> {
+        match digit {
+            0 => Some(ParameterMode::Position),
+            1 => Some(ParameterMode::Immediate),
+            2 => Some(ParameterMode::Relative),
+            _ => None,
+        }
+    }
+    pub fn digit(self) -> i64 {
+        match self {
+            ParameterMode::Position => 0,
+            ParameterMode::Immediate => 1,
+            ParameterMode::Relative => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Instruction {
     opcode: OpCode,
     params: (ParameterMode, ParameterMode, ParameterMode),
 }
 
-impl From<i64> for Instruction {
-    fn from(x: i64) -> Instruction {
-        let s = format!("{:05}", x);
-        let opcode = OpCode::from(&s[3..]);
-        let mut chars = s.chars();
-        let a = ParameterMode::from(chars.next().unwrap());
-        let b = ParameterMode::from(chars.next().unwrap());
-        let c = ParameterMode::from(chars.next().unwrap());
-        let params = (c, b, a);
-        Instruction { opcode, params }
+impl Instruction {
+    pub fn decode(x: i64, address: i64) -> Result<Instruction, ComputerError> {
+        if !(0..100_000).contains(&x) {
+            return Err(ComputerError::UnknownOpCode { opcode: x, address });
+        }
+        let opcode = OpCode::from_code(x % 100).ok_or(ComputerError::UnknownOpCode {
+            opcode: x % 100,
+            address,
+        })?;
+        let mode = |place: i64| {
+            let digit = x / place % 10;
+            ParameterMode::from_digit(digit).ok_or(ComputerError::BadParameterMode {
+                mode: digit,
+                address,
+            })
+        };
+        // the highest digit is checked first, matching the order the digits are written in
+        let c = mode(10_000)?;
+        let b = mode(1_000)?;
+        let a = mode(100)?;
+        let params = (a, b, c);
+        Ok(Instruction { opcode, params })
+    }
+    pub fn new(opcode: OpCode, modes: &[ParameterMode]) -> Instruction {
+        let mode = |i: usize| modes.get(i).cloned().unwrap_or(ParameterMode::Position);
+        Instruction {
+            opcode,
+            params: (mode(0), mode(1), mode(2)),
+        }
+    }
+    pub fn encode(&self) -> i64 {
+        self.modes()
+            .iter()
+            .rev()
+            .fold(0, |result, mode| result * 10 + mode.digit())
+            * 100
+            + self.opcode.code()
+    }
+    pub fn opcode(&self) -> OpCode {
+        self.opcode
+    }
+    pub fn modes(&self) -> [ParameterMode; 3] {
+        [self.params.0, self.params.1, self.params.2]
+    }
+}
+
+// generic over the word so wider computers can hand back their own outputs
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum State<W = i64> {
+    Output(W),
+    NeedsInput,
+    Halted,
+}
+
+impl<W> State<W> {
+    pub fn output(self) -> Option<W> {
+        match self {
+            State::Output(x) => Some(x),
+            State::NeedsInput | State::Halted => None,
+        }
     }
 }
 
 #[derive(Default, Debug)]
-pub struct Computer {
-    pub program: HashMap<i64, i64>,
-    original_program: HashMap<i64, i64>,
+pub struct Computer<M: Memory = DenseMemory> {
+    pub program: M,
+    original_program: M,
     pub input_queue: VecDeque<i64>,
     pub yield_on_empty: bool,
     default_input: i64,
     pos: i64,
     relative_base: i64,
+    tracer: Option<Tracer>,
+    profiler: Option<Profiler>,
+    history: Option<History>,
+    decode_cache: DecodeCache,
+    stats: Stats,
+    // instructions left before the computer refuses to go on, unlimited if None
+    budget: Option<u64>,
+    checked: bool,
 }
 
 impl Computer {
     pub fn new(data: &Vec<i64>, input: &[i64]) -> Self {
-        let program = data
-            .iter()
-            .enumerate()
-            .fold(HashMap::new(), |mut result, (i, &x)| {
-                result.insert(i as i64, x);
-                result
-            });
+        Computer::with_memory(data, input)
+    }
+    pub fn load_data(day: usize) -> Result<Vec<i64>, ComputerError> {
+        Computer::parse_program(&super::get_input::main(day))
+    }
+    pub fn parse_program(text: &str) -> Result<Vec<i64>, ComputerError> {
+        parse_words(text)
+    }
+}
+
+// comma separated values of any type, reporting the line and column of the first bad one
+fn parse_words<W: FromStr>(text: &str) -> Result<Vec<W>, ComputerError> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+    for token in text.trim_end().split(',') {
+        let start = offset + token.len() - token.trim_start().len();
+        offset += token.len() + 1;
+        match token.trim().parse() {
+            Ok(value) => result.push(value),
+            Err(_) => {
+                let before = &text[..start];
+                return Err(ComputerError::Parse {
+                    line: before.matches('\n').count() + 1,
+                    column: start - before.rfind('\n').map_or(0, |i| i + 1) + 1,
+                    token: token.trim().to_string(),
+                });
+            }
+        }
+    }
+    Ok(result)
+}
+
+impl<M: Memory> Computer<M> {
+    pub fn with_memory(data: &[i64], input: &[i64]) -> Self {
+        let program = M::from_program(data);
         Computer {
             program: program.clone(),
             original_program: program,
-            input_queue: input.into_iter().cloned().collect(),
+            input_queue: input.iter().cloned().collect(),
             yield_on_empty: false,
             default_input: 0,
             pos: 0,
             relative_base: 0,
+            tracer: None,
+            profiler: None,
+            history: None,
+            decode_cache: DecodeCache::default(),
+            stats: Stats::default(),
+            budget: None,
+            checked: false,
         }
     }
-    pub fn ascii_to_intcode(x: &Vec<&str>) -> Vec<i64> {
-        format!("{}\n", x.join("\n"))
-            .chars()
-            .map(|ch| ch as i64)
-            .collect()
-    }
-    pub fn intcode_to_ascii(x: &Vec<i64>) -> String {
-        x[0..x.len() - 1]
-            .iter()
-            .cloned()
-            .map(|z| z as u8 as char)
-            .collect()
-    }
-    pub fn load_data(day: usize) -> Vec<i64> {
-        super::get_input::main(day)
-            .trim()
-            .split(",")
-            .map(|z| i64::from_str_radix(z, 10).unwrap())
-            .collect()
-    }
     pub fn reset(&mut self) {
         self.program = self.original_program.clone();
+        self.decode_cache.clear();
         self.input_queue.clear();
         self.default_input = 0;
         self.pos = 0;
         self.relative_base = 0;
+        self.stats = Stats::default();
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
+    }
+    pub fn save(&self) -> Snapshot<M> {
+        Snapshot {
+            memory: self.program.clone(),
+            input_queue: self.input_queue.clone(),
+            yield_on_empty: self.yield_on_empty,
+            default_input: self.default_input,
+            pos: self.pos,
+            relative_base: self.relative_base,
+        }
+    }
+    pub fn restore(&mut self, snapshot: &Snapshot<M>) {
+        self.program = snapshot.memory.clone();
+        self.decode_cache.clear();
+        self.input_queue = snapshot.input_queue.clone();
+        self.yield_on_empty = snapshot.yield_on_empty;
+        self.default_input = snapshot.default_input;
+        self.pos = snapshot.pos;
+        self.relative_base = snapshot.relative_base;
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
+    }
+    // overwrites cells of the program itself, so the patch also survives reset(). nothing is
+    // written if any of the addresses is negative
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<(), ComputerError> {
+        if let Some(&address) = patch.cells.keys().find(|&&address| address < 0) {
+            return Err(ComputerError::NegativeAddress { address });
+        }
+        for (&address, &value) in patch.cells.iter() {
+            self.program.set(address, value);
+            self.original_program.set(address, value);
+        }
+        self.decode_cache.clear();
+        Ok(())
+    }
+    // the snapshot's memory also becomes the program that reset() returns to
+    pub fn from_snapshot(snapshot: &Snapshot<M>) -> Self {
+        let mut computer: Computer<M> = Default::default();
+        computer.restore(snapshot);
+        computer.original_program = computer.program.clone();
+        computer
     }
     pub fn get_program(&self) -> Vec<i64> {
-        let len = *self.program.keys().max().unwrap();
-        let mut result = vec![0; (len + 1) as usize];
-        for (&i, &v) in self.program.iter() {
+        let cells = self.program.cells();
+        let mut result = vec![0; cells.last().map_or(0, |&(i, _)| i as usize + 1)];
+        for (i, v) in cells {
             result[i as usize] = v;
         }
         result
     }
+    pub fn peek(&self, address: i64) -> i64 {
+        self.program.get(address)
+    }
+    pub fn pos(&self) -> i64 {
+        self.pos
+    }
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+    pub fn set_profiler(&mut self, profiler: Profiler) {
+        self.profiler = Some(profiler);
+    }
+    pub fn take_profiler(&mut self) -> Option<Profiler> {
+        self.profiler.take()
+    }
+    // records every instruction from now on so the computer can be run backwards
+    pub fn set_history(&mut self, history: History) {
+        self.history = Some(history);
+    }
+    pub fn take_history(&mut self) -> Option<History> {
+        self.history.take()
+    }
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+    // undoes the last recorded instruction. input it took off the input queue goes back on
+    // the front of the queue, input read from a device is only handed back
+    pub fn step_back(&mut self) -> Option<Undone> {
+        let entry = self.history.as_mut()?.pop()?;
+        if let Some((address, value)) = entry.write {
+            self.decode_cache.invalidate(address);
+            self.program.set(address, value);
+        }
+        if let Some((value, true)) = entry.input {
+            self.input_queue.push_front(value);
+        }
+        self.pos = entry.address;
+        self.relative_base = entry.relative_base;
+        Some(entry.undone())
+    }
+    // undoes up to steps instructions, returning them latest first
+    pub fn rewind(&mut self, steps: usize) -> Vec<Undone> {
+        (0..steps).map_while(|_| self.step_back()).collect()
+    }
+    // rewinds to just before the last recorded write to address, if there is one
+    pub fn rewind_to_write(&mut self, address: i64) -> Option<Vec<Undone>> {
+        let steps = self.history.as_ref()?.last_write(address)?;
+        Some(self.rewind(steps))
+    }
     pub fn add_input(&mut self, input: &[i64]) {
         self.input_queue.extend(input);
     }
     pub fn set_default_input(&mut self, value: i64) {
         self.default_input = value;
     }
-    fn get_instruction(&self) -> Instruction {
-        Instruction::from(self.program[&self.pos])
+    // on by default, turning it off decodes every instruction as it is executed
+    pub fn set_decode_cache(&mut self, enabled: bool) {
+        self.decode_cache.enabled = enabled;
+        self.decode_cache.clear();
+    }
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+    // allows this many more instructions, after which running fails with
+    // ComputerError::BudgetExceeded until the budget is raised again
+    pub fn set_budget(&mut self, instructions: Option<u64>) {
+        self.budget = instructions;
+    }
+    pub fn budget(&self) -> Option<u64> {
+        self.budget
+    }
+    // makes add and mul fail with ComputerError::Overflow instead of wrapping around
+    pub fn set_checked_arithmetic(&mut self, checked: bool) {
+        self.checked = checked;
+    }
+    fn arithmetic(
+        &self,
+        value: Option<i64>,
+        unchecked: impl FnOnce() -> i64,
+    ) -> Result<i64, ComputerError> {
+        if !self.checked {
+            return Ok(unchecked());
+        }
+        value.ok_or(ComputerError::Overflow { address: self.pos })
     }
-    fn get_value(&mut self, index: i64, mode: ParameterMode) -> i64 {
-        let a = *self.program.entry(index).or_insert(0);
-        match mode {
-            ParameterMode::Position => *self.program.entry(a).or_insert(0),
+    fn load(&mut self, address: i64) -> Result<i64, ComputerError> {
+        if address < 0 {
+            return Err(ComputerError::NegativeAddress { address });
+        }
+        self.stats.touch(address);
+        Ok(self.program.get(address))
+    }
+    fn get_instruction(&mut self) -> Result<Instruction, ComputerError> {
+        let value = self.load(self.pos)?;
+        if let Some(instruction) = self.decode_cache.get(self.pos, value) {
+            return Ok(instruction);
+        }
+        let instruction = Instruction::decode(value, self.pos)?;
+        self.decode_cache.insert(self.pos, value, instruction);
+        Ok(instruction)
+    }
+    fn get_value(&mut self, index: i64, mode: ParameterMode) -> Result<i64, ComputerError> {
+        let a = self.load(index)?;
+        let address = match mode {
+            ParameterMode::Position => a,
+            ParameterMode::Immediate => index,
+            ParameterMode::Relative => self.relative_base.wrapping_add(a),
+        };
+        let value = match mode {
             ParameterMode::Immediate => a,
-            ParameterMode::Relative => *self.program.entry(self.relative_base + a).or_insert(0),
+            _ => self.load(address)?,
+        };
+        if let Some(profiler) = self.profiler.as_mut().filter(|p| p.coverage()) {
+            if mode != ParameterMode::Immediate {
+                profiler.read(address);
+            }
+        }
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.reads.push(value);
         }
+        Ok(value)
     }
-    fn write(&mut self, index: i64, value: i64, mode: ParameterMode) {
-        let a = *self.program.entry(index).or_insert(0);
-        match mode {
-            ParameterMode::Immediate => panic!("immediate mode not supported for writing"),
-            ParameterMode::Position => self.program.insert(a, value),
-            ParameterMode::Relative => self.program.insert(a + self.relative_base, value),
+    fn write_address(&mut self, index: i64, mode: ParameterMode) -> Result<i64, ComputerError> {
+        let a = self.load(index)?;
+        let address = match mode {
+            ParameterMode::Immediate => {
+                return Err(ComputerError::ImmediateWrite { address: self.pos })
+            }
+            ParameterMode::Position => a,
+            ParameterMode::Relative => a.wrapping_add(self.relative_base),
         };
+        if address < 0 {
+            return Err(ComputerError::NegativeAddress { address });
+        }
+        Ok(address)
     }
-}
-
-impl<'a> Iterator for Computer {
-    type Item = i64;
-
-    fn next(&mut self) -> Option<i64> {
+    fn set_value(
+        &mut self,
+        index: i64,
+        value: i64,
+        mode: ParameterMode,
+    ) -> Result<(), ComputerError> {
+        let address = self.write_address(index, mode)?;
+        self.store(address, value);
+        Ok(())
+    }
+    fn store(&mut self, address: i64, value: i64) {
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.writes.push((address, value));
+        }
+        self.stats.touch(address);
+        if let Some(history) = self.history.as_mut() {
+            history.pending.write = Some((address, self.program.get(address)));
+        }
+        if let Some(profiler) = self.profiler.as_mut().filter(|p| p.coverage()) {
+            profiler.write(address);
+        }
+        self.decode_cache.invalidate(address);
+        self.program.set(address, value);
+    }
+    pub fn run(&mut self) -> Result<State, ComputerError> {
         loop {
-            let instruction = self.get_instruction();
-            match instruction.opcode {
-                OpCode::Add => {
-                    let a = self.get_value(self.pos + 1, instruction.params.0);
-                    let b = self.get_value(self.pos + 2, instruction.params.1);
-                    self.write(self.pos + 3, a + b, instruction.params.2);
-                    self.pos += 4;
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+    // runs with input and output going through the device instead of the input queue, until
+    // the program halts or the device has no more input
+    pub fn run_device<D: InputDevice + OutputDevice + ?Sized>(
+        &mut self,
+        device: &mut D,
+    ) -> Result<State, ComputerError> {
+        let mut io = Attached(device);
+        loop {
+            if let Some(state) = self.step_io(&mut io)? {
+                return Ok(state);
+            }
+        }
+    }
+    pub fn run_devices<I: InputDevice + ?Sized, O: OutputDevice + ?Sized>(
+        &mut self,
+        input: &mut I,
+        output: &mut O,
+    ) -> Result<State, ComputerError> {
+        self.run_device(&mut Split { input, output })
+    }
+    // executes one instruction, returns None if execution can simply continue
+    pub fn step(&mut self) -> Result<Option<State>, ComputerError> {
+        self.step_io(&mut Queue)
+    }
+    fn step_io<I: Io<M>>(&mut self, io: &mut I) -> Result<Option<State>, ComputerError> {
+        if self.budget == Some(0) {
+            return Err(ComputerError::BudgetExceeded { address: self.pos });
+        }
+        let address = self.pos;
+        let instruction = self.get_instruction()?;
+        if let Some(history) = self.history.as_mut() {
+            history.begin(address, self.relative_base);
+        }
+        let result = self.step_traced(instruction, io);
+        if let Ok(None) | Ok(Some(State::Output(_))) | Ok(Some(State::Halted)) = result {
+            if let Some(history) = self.history.as_mut() {
+                history.commit();
+            }
+            self.stats.instructions += 1;
+            if let Some(budget) = self.budget.as_mut() {
+                *budget -= 1;
+            }
+            if let Some(profiler) = self.profiler.as_mut() {
+                profiler.record(address, &instruction, self.pos);
+            }
+        }
+        result
+    }
+    fn step_traced<I: Io<M>>(
+        &mut self,
+        instruction: Instruction,
+        io: &mut I,
+    ) -> Result<Option<State>, ComputerError> {
+        if self.tracer.is_none() {
+            return self.execute(instruction, io);
+        }
+        let (address, relative_base) = (self.pos, self.relative_base);
+        let params: Vec<i64> = (1..=instruction.opcode.parameter_count() as i64)
+            .map(|i| self.peek(address + i))
+            .collect();
+        let result = self.execute(instruction, io);
+        let tracer = self.tracer.as_mut().unwrap();
+        match result {
+            Ok(Some(State::NeedsInput)) | Err(_) => {
+                tracer.reads.clear();
+                tracer.writes.clear();
+            }
+            Ok(_) => {
+                let changed = Some(self.relative_base).filter(|&rb| rb != relative_base);
+                tracer
+                    .record(address, &instruction, &params, changed)
+                    .map_err(|error| ComputerError::Trace(error.to_string()))?;
+            }
+        }
+        result
+    }
+    fn execute<I: Io<M>>(
+        &mut self,
+        instruction: Instruction,
+        io: &mut I,
+    ) -> Result<Option<State>, ComputerError> {
+        match instruction.opcode {
+            OpCode::Add => {
+                let a = self.get_value(self.pos + 1, instruction.params.0)?;
+                let b = self.get_value(self.pos + 2, instruction.params.1)?;
+                let value = self.arithmetic(a.checked_add(b), || a.wrapping_add(b))?;
+                self.set_value(self.pos + 3, value, instruction.params.2)?;
+                self.pos += 4;
+            }
+            OpCode::Multiply => {
+                let a = self.get_value(self.pos + 1, instruction.params.0)?;
+                let b = self.get_value(self.pos + 2, instruction.params.1)?;
+                let value = self.arithmetic(a.checked_mul(b), || a.wrapping_mul(b))?;
+                self.set_value(self.pos + 3, value, instruction.params.2)?;
+                self.pos += 4;
+            }
+            OpCode::JumpIfTrue => {
+                let a = self.get_value(self.pos + 1, instruction.params.0)?;
+                if a != 0 {
+                    self.pos = self.get_value(self.pos + 2, instruction.params.1)?;
+                } else {
+                    self.pos += 3;
                 }
-                OpCode::Multiply => {
-                    let a = self.get_value(self.pos + 1, instruction.params.0);
-                    let b = self.get_value(self.pos + 2, instruction.params.1);
-                    self.write(self.pos + 3, a * b, instruction.params.2);
-                    self.pos += 4;
+            }
+            OpCode::JumpIfFalse => {
+                let a = self.get_value(self.pos + 1, instruction.params.0)?;
+                if a == 0 {
+                    self.pos = self.get_value(self.pos + 2, instruction.params.1)?;
+                } else {
+                    self.pos += 3;
                 }
-                OpCode::JumpIfTrue => {
-                    let a = self.get_value(self.pos + 1, instruction.params.0);
-                    if a != 0 {
-                        self.pos = self.get_value(self.pos + 2, instruction.params.1);
+            }
+            OpCode::LessThan => {
+                let a = self.get_value(self.pos + 1, instruction.params.0)?;
+                let b = self.get_value(self.pos + 2, instruction.params.1)?;
+                let value = {
+                    if a < b {
+                        1
                     } else {
-                        self.pos += 3;
+                        0
                     }
-                }
-                OpCode::JumpIfFalse => {
-                    let a = self.get_value(self.pos + 1, instruction.params.0);
-                    if a == 0 {
-                        self.pos = self.get_value(self.pos + 2, instruction.params.1);
+                };
+                self.set_value(self.pos + 3, value, instruction.params.2)?;
+                self.pos += 4;
+            }
+            OpCode::Equals => {
+                let a = self.get_value(self.pos + 1, instruction.params.0)?;
+                let b = self.get_value(self.pos + 2, instruction.params.1)?;
+                let value = {
+                    if a == b {
+                        1
                     } else {
-                        self.pos += 3;
+                        0
                     }
+                };
+                self.set_value(self.pos + 3, value, instruction.params.2)?;
+                self.pos += 4;
+            }
+            OpCode::Input => {
+                let address = self.write_address(self.pos + 1, instruction.params.0)?;
+                let queued = I::QUEUED && !self.input_queue.is_empty();
+                let value = match io.input(self) {
+                    Some(value) => value,
+                    None => return Ok(Some(State::NeedsInput)),
+                };
+                if let Some(history) = self.history.as_mut() {
+                    history.pending.input = Some((value, queued));
                 }
-                OpCode::LessThan => {
-                    let a = self.get_value(self.pos + 1, instruction.params.0);
-                    let b = self.get_value(self.pos + 2, instruction.params.1);
-                    let value = {
-                        if a < b {
-                            1
-                        } else {
-                            0
-                        }
-                    };
-                    self.write(self.pos + 3, value, instruction.params.2);
-                    self.pos += 4;
-                }
-                OpCode::Equals => {
-                    let a = self.get_value(self.pos + 1, instruction.params.0);
-                    let b = self.get_value(self.pos + 2, instruction.params.1);
-                    let value = {
-                        if a == b {
-                            1
-                        } else {
-                            0
-                        }
-                    };
-                    self.write(self.pos + 3, value, instruction.params.2);
-                    self.pos += 4;
-                }
-                OpCode::Input => {
-                    if self.yield_on_empty && self.input_queue.is_empty() {
-                        return None;
-                    }
-                    let value = self.input_queue.pop_front().unwrap_or(self.default_input);
-                    self.write(self.pos + 1, value, instruction.params.0);
-                    self.pos += 2;
-                }
-                OpCode::Output => {
-                    let a = self.get_value(self.pos + 1, instruction.params.0);
-                    self.pos += 2;
-                    return Some(a);
-                }
-                OpCode::RelativeBaseOffset => {
-                    let a = self.get_value(self.pos + 1, instruction.params.0);
-                    self.pos += 2;
-                    self.relative_base += a;
+                self.store(address, value);
+                self.stats.inputs += 1;
+                self.pos += 2;
+            }
+            OpCode::Output => {
+                let a = self.get_value(self.pos + 1, instruction.params.0)?;
+                self.pos += 2;
+                self.stats.outputs += 1;
+                if let Some(history) = self.history.as_mut() {
+                    history.pending.output = Some(a);
                 }
-                OpCode::Stop => {
-                    return None;
+                if io.output(a) {
+                    return Ok(Some(State::Output(a)));
                 }
             }
+            OpCode::RelativeBaseOffset => {
+                let a = self.get_value(self.pos + 1, instruction.params.0)?;
+                self.pos += 2;
+                self.relative_base = self.relative_base.wrapping_add(a);
+            }
+            OpCode::Stop => {
+                return Ok(Some(State::Halted));
+            }
         }
+        Ok(None)
+    }
+}
+
+impl<M: Memory> Iterator for Computer<M> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        self.run().ok().and_then(State::output)
     }
 }
 
@@ -266,7 +767,100 @@ mod tests {
             },
         )];
         for (input, answer) in values {
-            assert_eq!(Instruction::from(input), answer);
+            assert_eq!(Instruction::decode(input, 0), Ok(answer));
+        }
+    }
+
+    // the string based decoder this module used to have, kept to check the arithmetic one
+    fn decode_text(x: i64, address: i64) -> Result<Instruction, ComputerError> {
+        if !(0..100_000).contains(&x) {
+            return Err(ComputerError::UnknownOpCode { opcode: x, address });
+        }
+        let s = format!("{:05}", x);
+        let opcode = OpCode::try_from(&s[3..])
+            .map_err(|opcode| ComputerError::UnknownOpCode { opcode, address })?;
+        let modes: Result<Vec<ParameterMode>, ComputerError> = s[..3]
+            .chars()
+            .map(|ch| {
+                ParameterMode::try_from(ch)
+                    .map_err(|mode| ComputerError::BadParameterMode { mode, address })
+            })
+            .collect();
+        let modes: Vec<ParameterMode> = modes?.into_iter().rev().collect();
+        Ok(Instruction::new(opcode, &modes))
+    }
+
+    #[test]
+    fn test_decode_matches_text_decoder() {
+        for x in -10..100_010 {
+            assert_eq!(Instruction::decode(x, 3), decode_text(x, 3), "{}", x);
+        }
+    }
+
+    #[test]
+    fn test_self_modifying_code() {
+        // the first pass through the loop turns the add at `patched` into a multiply
+        let program = asm::assemble(
+            "
+            loop:
+            patched:
+                add x, #3, x
+                out x
+                add #1002, #0, patched
+                lt x, #20, flag
+                jnz flag, #loop
+                hlt
+            x:    db 1
+            flag: db 0
+            ",
+        )
+        .unwrap();
+        for &cached in [true, false].iter() {
+            let mut computer = Computer::new(&program, &[]);
+            computer.set_decode_cache(cached);
+            assert_eq!(computer.by_ref().collect::<Vec<i64>>(), vec![4, 12, 36]);
+            computer.reset();
+            assert_eq!(computer.collect::<Vec<i64>>(), vec![4, 12, 36]);
+        }
+        // edits made directly to memory rather than by the program are picked up too
+        let program = asm::assemble("loop: out #1\njnz #1, #loop").unwrap();
+        let mut computer = Computer::new(&program, &[]);
+        assert_eq!(computer.next(), Some(1));
+        assert_eq!(computer.next(), Some(1));
+        computer.program.set(0, 99);
+        assert_eq!(computer.next(), None);
+    }
+
+    // runs every intcode day's program with and without the decode cache in lockstep, feeding
+    // both the same stream of small inputs, and checks they never disagree
+    #[test]
+    fn test_decode_cache_on_every_day() {
+        for &day in [2, 5, 7, 9, 11, 13, 15, 17, 19, 21, 23, 25].iter() {
+            let program = Computer::load_data(day).unwrap();
+            let mut seed: i64 = day as i64;
+            let input: Vec<i64> = (0..1000)
+                .map(|_| {
+                    seed = (seed * 1_103_515_245 + 12_345) % (1 << 31);
+                    seed >> 16 & 3
+                })
+                .collect();
+            let mut cached = Computer::new(&program, &input);
+            let mut uncached = Computer::new(&program, &input);
+            uncached.set_decode_cache(false);
+            for _ in 0..200_000 {
+                let state = cached.step();
+                assert_eq!(state, uncached.step(), "day {}", day);
+                assert_eq!(
+                    (cached.pos(), cached.relative_base()),
+                    (uncached.pos(), uncached.relative_base()),
+                    "day {}",
+                    day
+                );
+                if let Err(_) | Ok(Some(State::Halted)) = state {
+                    break;
+                }
+            }
+            assert_eq!(cached.get_program(), uncached.get_program(), "day {}", day);
         }
     }
 
@@ -340,4 +934,174 @@ mod tests {
             assert_eq!(Computer::new(&program, &[]).collect::<Vec<i64>>(), output);
         }
     }
+
+    #[test]
+    fn test_run_states() {
+        let program = asm::assemble(
+            "
+                in value
+                out value
+                in value
+                out value
+                hlt
+            value: db 0
+            ",
+        )
+        .unwrap();
+        let mut computer = Computer::new(&program, &[7]);
+        computer.yield_on_empty = true;
+        assert_eq!(computer.run(), Ok(State::Output(7)));
+        assert_eq!(computer.run(), Ok(State::NeedsInput));
+        assert_eq!(computer.run(), Ok(State::NeedsInput));
+        computer.add_input(&[8]);
+        assert_eq!(computer.run(), Ok(State::Output(8)));
+        assert_eq!(computer.run(), Ok(State::Halted));
+        assert_eq!(computer.run(), Ok(State::Halted));
+    }
+
+    #[test]
+    fn test_step() {
+        let program = asm::assemble("add #2, #3, sum\nout #5\nhlt\nsum: db 0").unwrap();
+        let mut computer = Computer::new(&program, &[]);
+        assert_eq!(computer.step(), Ok(None));
+        assert_eq!(computer.get_program()[7], 5);
+        assert_eq!(computer.step(), Ok(Some(State::Output(5))));
+        assert_eq!(computer.step(), Ok(Some(State::Halted)));
+    }
+
+    #[test]
+    fn test_malformed_programs() {
+        let values: Vec<(Vec<i64>, ComputerError)> = vec![
+            (
+                vec![1, 0, 0, 0, 42],
+                ComputerError::UnknownOpCode {
+                    opcode: 42,
+                    address: 4,
+                },
+            ),
+            (
+                vec![30001, 0, 0, 0, 99],
+                ComputerError::BadParameterMode {
+                    mode: 3,
+                    address: 0,
+                },
+            ),
+            (
+                vec![11101, 1, 1, 0, 99],
+                ComputerError::ImmediateWrite { address: 0 },
+            ),
+            (
+                vec![1, -5, 0, 0, 99],
+                ComputerError::NegativeAddress { address: -5 },
+            ),
+            (
+                vec![1106, 0, -1],
+                ComputerError::NegativeAddress { address: -1 },
+            ),
+        ];
+        for (program, error) in values {
+            let mut computer = Computer::new(&program, &[]);
+            assert_eq!(computer.run(), Err(error.clone()));
+            assert_eq!(computer.run(), Err(error));
+            assert_eq!(computer.next(), None);
+        }
+    }
+
+    #[test]
+    fn test_failed_input_is_not_consumed() {
+        let mut computer = Computer::new(&vec![103, 0, 99], &[5]);
+        assert!(computer.run().is_err());
+        assert_eq!(computer.input_queue, vec![5]);
+    }
+
+    #[test]
+    fn test_parse_program() {
+        assert_eq!(
+            Computer::parse_program("1,0, -3,\n4,99\n"),
+            Ok(vec![1, 0, -3, 4, 99])
+        );
+        assert_eq!(
+            Computer::parse_program("1,2,3,\n4, x5,99"),
+            Err(ComputerError::Parse {
+                line: 2,
+                column: 4,
+                token: "x5".to_string(),
+            })
+        );
+        assert_eq!(
+            Computer::parse_program("1,,2"),
+            Err(ComputerError::Parse {
+                line: 1,
+                column: 3,
+                token: "".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_budget() {
+        // jnz #1, #0 loops forever
+        let mut computer = Computer::new(&vec![1105, 1, 0], &[]);
+        computer.set_budget(Some(1000));
+        assert_eq!(
+            computer.run(),
+            Err(ComputerError::BudgetExceeded { address: 0 })
+        );
+        assert_eq!(computer.next(), None);
+        assert_eq!(computer.stats().instructions, 1000);
+        assert_eq!(computer.budget(), Some(0));
+
+        // raising the budget picks up where it stopped
+        let mut computer = Computer::new(&vec![4, 0, 4, 2, 99], &[]);
+        computer.set_budget(Some(1));
+        assert_eq!(computer.run(), Ok(State::Output(4)));
+        assert!(computer.run().is_err());
+        computer.set_budget(None);
+        assert_eq!(computer.run(), Ok(State::Output(4)));
+        assert_eq!(computer.run(), Ok(State::Halted));
+    }
+
+    #[test]
+    fn test_unchecked_arithmetic_wraps() {
+        let program = vec![1101, i64::MAX, 1, 0, 1102, i64::MAX, 2, 1, 99];
+        let mut computer = Computer::new(&program, &[]);
+        assert_eq!(computer.run(), Ok(State::Halted));
+        assert_eq!((computer.peek(0), computer.peek(1)), (i64::MIN, -2));
+        computer.reset();
+        computer.set_checked_arithmetic(true);
+        assert_eq!(computer.run(), Err(ComputerError::Overflow { address: 0 }));
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut computer = Computer::new(&Computer::load_data(9).unwrap(), &[1]);
+        assert_eq!(computer.by_ref().count(), 1);
+        let stats = computer.stats();
+        assert_eq!((stats.inputs, stats.outputs), (1, 1));
+        assert!(stats.instructions > 0);
+        assert!(stats.max_address >= computer.get_program().len() as i64 - 1);
+        assert!(stats
+            .to_string()
+            .starts_with(&format!("{} instructions, 1 inputs", stats.instructions)));
+
+        // waiting for input doesn't count as an instruction
+        let mut computer = Computer::new(&vec![3, 5, 4, 5, 99], &[]);
+        computer.yield_on_empty = true;
+        assert_eq!(computer.run(), Ok(State::NeedsInput));
+        assert_eq!(computer.stats().instructions, 0);
+        computer.add_input(&[7]);
+        assert_eq!(computer.run(), Ok(State::Output(7)));
+        assert_eq!(computer.run(), Ok(State::Halted));
+        assert_eq!(
+            computer.stats(),
+            Stats {
+                instructions: 3,
+                inputs: 1,
+                outputs: 1,
+                max_address: 5,
+            }
+        );
+        computer.reset();
+        assert_eq!(computer.stats(), Stats::default());
+    }
 }
@@ -0,0 +1,438 @@
+use std::error::Error;
+use std::fmt;
+
+use super::capture::Capture;
+use super::{Computer, ComputerError, State};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Packet {
+    pub source: i64,
+    pub destination: i64,
+    pub values: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Control {
+    Continue,
+    Send(Packet),
+    Stop(Packet),
+}
+
+// sees every packet before it is routed and decides what to do when the network goes idle.
+// packets for addresses that have no node are dropped unless the monitor does something
+pub trait Monitor {
+    fn packet(&mut self, _packet: &Packet) -> Control {
+        Control::Continue
+    }
+    fn idle(&mut self) -> Control {
+        Control::Continue
+    }
+}
+
+// stops the network with the first packet sent to its address
+pub struct FirstPacket {
+    address: i64,
+}
+
+impl FirstPacket {
+    pub fn new(address: i64) -> Self {
+        FirstPacket { address }
+    }
+}
+
+impl Monitor for FirstPacket {
+    fn packet(&mut self, packet: &Packet) -> Control {
+        if packet.destination == self.address {
+            Control::Stop(packet.clone())
+        } else {
+            Control::Continue
+        }
+    }
+}
+
+// keeps the last packet sent to its address and passes it on to node 0 whenever the network
+// is idle, stopping once it would pass on the same y value twice in a row
+pub struct Nat {
+    address: i64,
+    // which of the packet's values has to repeat
+    key: usize,
+    last: Option<Packet>,
+    sent: Option<i64>,
+}
+
+impl Nat {
+    pub fn new(address: i64) -> Self {
+        Nat {
+            address,
+            key: 1,
+            last: None,
+            sent: None,
+        }
+    }
+    // a key past the network's arity is never delivered, so run fails with Deadlock
+    pub fn with_key(mut self, key: usize) -> Self {
+        self.key = key;
+        self
+    }
+}
+
+impl Monitor for Nat {
+    fn packet(&mut self, packet: &Packet) -> Control {
+        if packet.destination == self.address {
+            self.last = Some(packet.clone());
+        }
+        Control::Continue
+    }
+    fn idle(&mut self) -> Control {
+        let packet = match self.last.take() {
+            Some(packet) => Packet {
+                source: self.address,
+                destination: 0,
+                values: packet.values,
+            },
+            None => return Control::Continue,
+        };
+        // a packet too short to hold the key could never repeat, so the network is left idle
+        // instead of being woken forever
+        let value = match packet.values.get(self.key) {
+            Some(&value) => value,
+            None => return Control::Continue,
+        };
+        if self.sent == Some(value) {
+            return Control::Stop(packet);
+        }
+        self.sent = Some(value);
+        Control::Send(packet)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Schedule {
+    RoundRobin,
+    // visits the nodes in a fresh shuffled order every round
+    Random(u64),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum NetworkError {
+    Computer { node: usize, error: ComputerError },
+    Deadlock,
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetworkError::Computer { node, error } => write!(f, "node {}: {}", node, error),
+            NetworkError::Deadlock => write!(f, "network is idle and the monitor sent nothing"),
+        }
+    }
+}
+
+impl Error for NetworkError {}
+
+#[derive(Debug)]
+struct Node {
+    computer: Computer,
+    outputs: Vec<i64>,
+    // consecutive turns spent reading the empty input with nothing to show for it
+    idle: usize,
+    halted: bool,
+}
+
+#[derive(Debug)]
+pub struct Network<T: Monitor> {
+    nodes: Vec<Node>,
+    monitor: T,
+    arity: usize,
+    schedule: Schedule,
+    idle_threshold: usize,
+    empty_input: i64,
+    capture: Option<Capture>,
+    tick: usize,
+}
+
+impl<T: Monitor> Network<T> {
+    // every node runs the same program and is told its address as its first input
+    pub fn new(program: &[i64], size: usize, monitor: T) -> Self {
+        let nodes = (0..size)
+            .map(|address| {
+                let mut computer = Computer::with_memory(program, &[address as i64]);
+                computer.yield_on_empty = true;
+                Node {
+                    computer,
+                    outputs: Vec::new(),
+                    idle: 0,
+                    halted: false,
+                }
+            })
+            .collect();
+        Network {
+            nodes,
+            monitor,
+            arity: 2,
+            schedule: Schedule::RoundRobin,
+            idle_threshold: 1,
+            empty_input: -1,
+            capture: None,
+            tick: 0,
+        }
+    }
+    pub fn with_arity(mut self, arity: usize) -> Self {
+        self.arity = arity;
+        if let Some(capture) = self.capture.as_mut() {
+            capture.arity = arity;
+        }
+        self
+    }
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+    pub fn with_idle_threshold(mut self, turns: usize) -> Self {
+        self.idle_threshold = turns;
+        self
+    }
+    pub fn with_empty_input(mut self, value: i64) -> Self {
+        self.empty_input = value;
+        self
+    }
+    // records every packet sent on the network, including ones the monitor sends
+    pub fn with_capture(mut self) -> Self {
+        self.capture = Some(Capture::new(self.arity));
+        self
+    }
+    pub fn capture(&self) -> Option<&Capture> {
+        self.capture.as_ref()
+    }
+    pub fn monitor(&self) -> &T {
+        &self.monitor
+    }
+    pub fn run(&mut self) -> Result<Packet, NetworkError> {
+        let mut order: Vec<usize> = (0..self.nodes.len()).collect();
+        let mut seed = match self.schedule {
+            Schedule::RoundRobin => 0,
+            Schedule::Random(seed) => seed,
+        };
+        loop {
+            if self.is_idle() {
+                match self.monitor.idle() {
+                    Control::Continue => return Err(NetworkError::Deadlock),
+                    Control::Send(packet) => {
+                        self.record(&packet);
+                        self.deliver(&packet);
+                    }
+                    Control::Stop(packet) => return Ok(packet),
+                }
+            }
+            if let Schedule::Random(_) = self.schedule {
+                for i in (1..order.len()).rev() {
+                    seed = seed
+                        .wrapping_mul(6_364_136_223_846_793_005)
+                        .wrapping_add(1_442_695_040_888_963_407);
+                    order.swap(i, (seed >> 33) as usize % (i + 1));
+                }
+            }
+            for &i in order.iter() {
+                if let Some(packet) = self.turn(i)? {
+                    if let Some(packet) = self.route(packet) {
+                        return Ok(packet);
+                    }
+                }
+            }
+            self.tick += 1;
+        }
+    }
+    fn is_idle(&self) -> bool {
+        self.nodes.iter().all(|node| {
+            node.halted
+                || (node.idle >= self.idle_threshold && node.computer.input_queue.is_empty())
+        })
+    }
+    // runs a node until it sends a packet or runs out of input
+    fn turn(&mut self, i: usize) -> Result<Option<Packet>, NetworkError> {
+        let empty_input = self.empty_input;
+        let node = &mut self.nodes[i];
+        if node.halted {
+            return Ok(None);
+        }
+        let starved = node.computer.input_queue.is_empty();
+        if starved {
+            node.computer.add_input(&[empty_input]);
+        }
+        loop {
+            match node.computer.run() {
+                Ok(State::Output(value)) => {
+                    node.idle = 0;
+                    node.outputs.push(value);
+                    if node.outputs.len() == self.arity + 1 {
+                        let values = node.outputs.split_off(1);
+                        return Ok(Some(Packet {
+                            source: i as i64,
+                            destination: node.outputs.pop().unwrap(),
+                            values,
+                        }));
+                    }
+                }
+                Ok(State::NeedsInput) => {
+                    if starved && node.outputs.is_empty() {
+                        node.idle += 1;
+                    } else {
+                        node.idle = 0;
+                    }
+                    return Ok(None);
+                }
+                Ok(State::Halted) => {
+                    node.halted = true;
+                    return Ok(None);
+                }
+                Err(error) => return Err(NetworkError::Computer { node: i, error }),
+            }
+        }
+    }
+    // returns the packet the monitor stopped the network with, if any
+    fn route(&mut self, packet: Packet) -> Option<Packet> {
+        self.record(&packet);
+        match self.monitor.packet(&packet) {
+            Control::Continue => (),
+            Control::Send(extra) => {
+                self.record(&extra);
+                self.deliver(&extra);
+            }
+            Control::Stop(packet) => return Some(packet),
+        }
+        self.deliver(&packet);
+        None
+    }
+    fn record(&mut self, packet: &Packet) {
+        if let Some(capture) = self.capture.as_mut() {
+            capture.record(self.tick, packet);
+        }
+    }
+    fn deliver(&mut self, packet: &Packet) {
+        if packet.destination < 0 {
+            return;
+        }
+        if let Some(node) = self.nodes.get_mut(packet.destination as usize) {
+            node.computer.add_input(&packet.values);
+            node.idle = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    // node 0 starts a count that every node increments and forwards to the next address
+    fn program() -> Vec<i64> {
+        assemble(
+            "
+                in addr
+                add addr, #1, next
+                jnz addr, #wait
+                out next
+                out #0
+            wait:
+                in value
+                eq value, #-1, flag
+                jnz flag, #wait
+                add value, #1, value
+                out next
+                out value
+                jnz #1, #wait
+            addr:  db 0
+            next:  db 0
+            value: db 0
+            flag:  db 0
+            ",
+        )
+        .unwrap()
+    }
+
+    #[derive(Default)]
+    struct Counter(usize);
+
+    impl Monitor for Counter {
+        fn packet(&mut self, _packet: &Packet) -> Control {
+            self.0 += 1;
+            Control::Continue
+        }
+    }
+
+    #[test]
+    fn test_ring() {
+        let expected = Packet {
+            source: 4,
+            destination: 5,
+            values: vec![4],
+        };
+        for &schedule in [
+            Schedule::RoundRobin,
+            Schedule::Random(1),
+            Schedule::Random(2),
+        ]
+        .iter()
+        {
+            let mut network = Network::new(&program(), 5, FirstPacket::new(5))
+                .with_arity(1)
+                .with_schedule(schedule);
+            assert_eq!(network.run(), Ok(expected.clone()));
+        }
+    }
+
+    #[test]
+    fn test_deadlock() {
+        let mut network = Network::new(&program(), 5, Counter::default()).with_arity(1);
+        assert_eq!(network.run(), Err(NetworkError::Deadlock));
+        assert_eq!(network.monitor().0, 5);
+
+        let mut network = Network::new(&program(), 5, Nat::new(7)).with_arity(1);
+        assert_eq!(network.run(), Err(NetworkError::Deadlock));
+        // the packets the nat gets only have an x, so it never has a y to compare
+        let mut network = Network::new(&program(), 5, Nat::new(5)).with_arity(1);
+        assert_eq!(network.run(), Err(NetworkError::Deadlock));
+    }
+
+    #[test]
+    fn test_nat() {
+        let packet = |values: Vec<i64>| Packet {
+            source: 3,
+            destination: 255,
+            values,
+        };
+        let sent = |values: Vec<i64>| Packet {
+            source: 255,
+            destination: 0,
+            values,
+        };
+        // only y has to repeat, x can change
+        let mut nat = Nat::new(255);
+        assert_eq!(nat.idle(), Control::Continue);
+        nat.packet(&packet(vec![1, 7]));
+        assert_eq!(nat.idle(), Control::Send(sent(vec![1, 7])));
+        nat.packet(&packet(vec![2, 7]));
+        assert_eq!(nat.idle(), Control::Stop(sent(vec![2, 7])));
+
+        let mut nat = Nat::new(255).with_key(0);
+        nat.packet(&packet(vec![1, 7]));
+        assert_eq!(nat.idle(), Control::Send(sent(vec![1, 7])));
+        nat.packet(&packet(vec![2, 7]));
+        assert_eq!(nat.idle(), Control::Send(sent(vec![2, 7])));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut network = Network::new(&[3, 5, 42], 2, Counter::default());
+        assert_eq!(
+            network.run(),
+            Err(NetworkError::Computer {
+                node: 0,
+                error: ComputerError::UnknownOpCode {
+                    opcode: 42,
+                    address: 2
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use std::thread;
+
+pub fn workers() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+// runs f on every index below count, each worker taking its own contiguous run of indices, and
+// keeps whatever it returns in index order
+pub fn map_indices<R, F>(count: usize, workers: usize, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(usize) -> Option<R> + Sync,
+{
+    let chunk_size = count.div_ceil(workers.max(1)).max(1);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..count)
+            .step_by(chunk_size)
+            .map(|start| {
+                let end = (start + chunk_size).min(count);
+                scope.spawn(move || (start..end).filter_map(f).collect::<Vec<R>>())
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_indices() {
+        let odd_squares = map_indices(10, 3, |i| Some(i * i).filter(|_| i % 2 == 1));
+        assert_eq!(odd_squares, vec![1, 9, 25, 49, 81]);
+        assert_eq!(map_indices(0, 4, Some), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::parallel;
+use super::{Computer, ComputerError, DenseMemory, Memory, State};
+
+// a named set of cells to overwrite before a program runs
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Patch {
+    pub name: String,
+    pub cells: BTreeMap<i64, i64>,
+}
+
+impl Patch {
+    pub fn new(name: &str) -> Self {
+        Patch {
+            name: name.to_string(),
+            cells: BTreeMap::new(),
+        }
+    }
+    pub fn set(mut self, address: i64, value: i64) -> Self {
+        self.cells.insert(address, value);
+        self
+    }
+    pub fn get(&self, address: i64) -> Option<i64> {
+        self.cells.get(&address).cloned()
+    }
+    fn merge(&self, other: &Patch) -> Patch {
+        let mut result = self.clone();
+        result.cells.extend(other.cells.iter());
+        result
+    }
+}
+
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cells: Vec<String> = self
+            .cells
+            .iter()
+            .map(|(address, value)| format!("[{}]={}", address, value))
+            .collect();
+        write!(f, "{}: {}", self.name, cells.join(" "))
+    }
+}
+
+// how one candidate ran, for the search predicate to judge
+#[derive(Debug)]
+pub struct Outcome {
+    pub patch: Patch,
+    // memory once the program stopped
+    pub memory: DenseMemory,
+    pub outputs: Vec<i64>,
+    // Halted, or NeedsInput if the program wanted more than the search gave it. an error if the
+    // patch couldn't be applied or the program failed
+    pub result: Result<State, ComputerError>,
+}
+
+impl Outcome {
+    pub fn peek(&self, address: i64) -> i64 {
+        self.memory.get(address)
+    }
+    pub fn halted(&self) -> bool {
+        self.result == Ok(State::Halted)
+    }
+}
+
+// runs a program once for every combination of values in the swept cells
+#[derive(Debug, Clone)]
+pub struct Search {
+    program: Vec<i64>,
+    input: Vec<i64>,
+    patch: Patch,
+    sweeps: Vec<(i64, RangeInclusive<i64>)>,
+    budget: Option<u64>,
+    workers: usize,
+}
+
+impl Search {
+    pub fn new(program: &[i64]) -> Self {
+        Search {
+            program: program.to_vec(),
+            input: Vec::new(),
+            patch: Patch::new("search"),
+            sweeps: Vec::new(),
+            budget: None,
+            workers: parallel::workers(),
+        }
+    }
+    pub fn with_input(mut self, input: &[i64]) -> Self {
+        self.input = input.to_vec();
+        self
+    }
+    // applied to every candidate, under the swept cells
+    pub fn with_patch(mut self, patch: &Patch) -> Self {
+        self.patch = self.patch.merge(patch);
+        self
+    }
+    // stops candidates that run too long, which then fail with BudgetExceeded
+    pub fn with_budget(mut self, instructions: u64) -> Self {
+        self.budget = Some(instructions);
+        self
+    }
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+    pub fn sweep(mut self, address: i64, values: RangeInclusive<i64>) -> Self {
+        self.sweeps.push((address, values));
+        self
+    }
+    fn size(values: &RangeInclusive<i64>) -> usize {
+        if values.is_empty() {
+            return 0;
+        }
+        (*values.end() as i128 - *values.start() as i128 + 1) as usize
+    }
+    pub fn candidate_count(&self) -> usize {
+        self.sweeps
+            .iter()
+            .map(|(_, values)| Search::size(values))
+            .fold(1, usize::saturating_mul)
+    }
+    // the candidate at index, reading it as a number whose digits are the sweeps, the last
+    // sweep changing fastest
+    pub fn candidate(&self, index: usize) -> Patch {
+        let mut patch = self.patch.clone();
+        let mut rest = index;
+        for (address, values) in self.sweeps.iter().rev() {
+            let size = Search::size(values);
+            let value = *values.start() as i128 + (rest % size) as i128;
+            patch = patch.set(*address, value as i64);
+            rest /= size;
+        }
+        patch
+    }
+    // every candidate in order, made as they are needed
+    pub fn candidates(&self) -> impl ExactSizeIterator<Item = Patch> + '_ {
+        (0..self.candidate_count()).map(move |i| self.candidate(i))
+    }
+    pub fn run(&self, patch: &Patch) -> Outcome {
+        let mut computer = Computer::new(&self.program, &self.input);
+        computer.yield_on_empty = true;
+        computer.set_budget(self.budget);
+        let mut outputs = Vec::new();
+        // a patch that can't be applied fails the candidate without running it
+        let result = computer.apply_patch(patch).and_then(|_| loop {
+            match computer.run() {
+                Ok(State::Output(value)) => outputs.push(value),
+                result => break result,
+            }
+        });
+        Outcome {
+            patch: patch.clone(),
+            memory: computer.program,
+            outputs,
+            result,
+        }
+    }
+    // the first candidate whose outcome satisfies the predicate
+    pub fn find<F>(&self, predicate: F) -> Option<Outcome>
+    where
+        F: Fn(&Outcome) -> bool + Sync,
+    {
+        // workers skip everything after the earliest match found so far
+        let first = AtomicUsize::new(self.candidate_count());
+        let found = parallel::map_indices(self.candidate_count(), self.workers, |i| {
+            if i > first.load(Ordering::Relaxed) {
+                return None;
+            }
+            let outcome = self.run(&self.candidate(i));
+            if !predicate(&outcome) {
+                return None;
+            }
+            first.fetch_min(i, Ordering::Relaxed);
+            Some(outcome)
+        });
+        found.into_iter().next()
+    }
+    // every candidate whose outcome satisfies the predicate, in order
+    pub fn find_all<F>(&self, predicate: F) -> Vec<Outcome>
+    where
+        F: Fn(&Outcome) -> bool + Sync,
+    {
+        parallel::map_indices(self.candidate_count(), self.workers, |i| {
+            Some(self.run(&self.candidate(i))).filter(|outcome| predicate(outcome))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    #[test]
+    fn test_apply_patch() {
+        let program = vec![1, 0, 0, 0, 99];
+        let mut computer = Computer::new(&program, &[]);
+        computer
+            .apply_patch(&Patch::new("double 99").set(1, 4).set(2, 4))
+            .unwrap();
+        computer.run().unwrap();
+        assert_eq!(computer.peek(0), 198);
+        // the patch is part of the program now, so it survives a reset
+        computer.reset();
+        assert_eq!(computer.peek(1), 4);
+        assert_eq!(
+            Patch::new("alarm").set(2, 2).set(1, 12).to_string(),
+            "alarm: [1]=12 [2]=2"
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        // outputs x * y + in, then hangs if x is 7
+        let program = assemble(
+            "
+                in c
+                mul x, y, z
+                add z, c, z
+                out z
+                eq x, #7, c
+            spin:
+                jnz c, #spin
+                hlt
+            x: db 0
+            y: db 0
+            z: db 0
+            c: db 0
+            ",
+        )
+        .unwrap();
+        let (x, y) = (program.len() as i64 - 4, program.len() as i64 - 3);
+        let search = Search::new(&program)
+            .with_input(&[1])
+            .with_budget(1000)
+            .with_workers(3)
+            .sweep(x, 0..=9)
+            .sweep(y, 0..=9);
+        assert_eq!(search.candidates().len(), 100);
+        let found = search.find(|outcome| outcome.outputs == vec![13]).unwrap();
+        assert_eq!((found.patch.get(x), found.patch.get(y)), (Some(2), Some(6)));
+        assert!(found.halted());
+        let all = search.find_all(|outcome| outcome.outputs == vec![13]);
+        let pairs: Vec<_> = all
+            .iter()
+            .map(|o| (o.patch.get(x).unwrap(), o.patch.get(y).unwrap()))
+            .collect();
+        assert_eq!(pairs, vec![(2, 6), (3, 4), (4, 3), (6, 2)]);
+        let hung = search.find_all(|outcome| outcome.result.is_err());
+        assert_eq!(hung.len(), 10);
+        assert!(hung.iter().all(|outcome| outcome.patch.get(x) == Some(7)));
+        assert!(search.find(|outcome| outcome.peek(x) == 10).is_none());
+        assert_eq!(search.candidate(26), search.candidates().nth(26).unwrap());
+        assert_eq!(
+            (search.candidate(26).get(x), search.candidate(26).get(y)),
+            (Some(2), Some(6))
+        );
+    }
+
+    #[test]
+    fn test_far_writes() {
+        // every candidate writes far past the program, which mustn't fill memory up to there
+        let program = assemble("add x, #0, 1099511627776\nhlt\nx: db 0").unwrap();
+        let x = program.len() as i64 - 1;
+        let outcomes = Search::new(&program)
+            .sweep(x, 0..=3)
+            .find_all(|outcome| outcome.halted());
+        assert_eq!(outcomes.len(), 4);
+        assert_eq!(outcomes[2].peek(1 << 40), 2);
+    }
+
+    #[test]
+    fn test_negative_addresses() {
+        let program = vec![99, 0];
+        let mut computer = Computer::new(&program, &[]);
+        let patch = Patch::new("bad").set(1, 5).set(-1, 5);
+        assert_eq!(
+            computer.apply_patch(&patch),
+            Err(ComputerError::NegativeAddress { address: -1 })
+        );
+        assert_eq!(computer.peek(1), 0);
+        let outcomes = Search::new(&program)
+            .sweep(-2, 0..=1)
+            .find_all(|outcome| outcome.result.is_err());
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(
+            outcomes[0].result,
+            Err(ComputerError::NegativeAddress { address: -2 })
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use super::ascii::{AsciiComputer, Reply};
+use super::snapshot::Snapshot;
+use super::ComputerError;
+
+// a game of an ascii program that remembers every command sent, so it can be saved and replayed
+pub struct Session {
+    pub computer: AsciiComputer,
+    script: Vec<String>,
+    macros: BTreeMap<String, Vec<String>>,
+}
+
+impl Session {
+    pub fn new(computer: AsciiComputer) -> Self {
+        Session {
+            computer,
+            script: Vec::new(),
+            macros: BTreeMap::new(),
+        }
+    }
+    // whatever the program prints before it first asks for input
+    pub fn start(&mut self) -> Result<Reply, ComputerError> {
+        self.computer.read()
+    }
+    pub fn command(&mut self, line: &str) -> Result<Reply, ComputerError> {
+        self.script.push(line.to_string());
+        self.computer.command(line)
+    }
+    // every command sent since the start, or since the game that was loaded started
+    pub fn script(&self) -> &[String] {
+        &self.script
+    }
+    pub fn define(&mut self, name: &str, commands: Vec<String>) {
+        self.macros.insert(name.to_string(), commands);
+    }
+    pub fn macros(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.macros
+    }
+    // the script as one command per line, which replays the game when fed back in
+    pub fn export(&self) -> String {
+        self.script
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect()
+    }
+    // the script with a "> " in front of each command, then a snapshot of the computer
+    pub fn save(&self) -> String {
+        let script: String = self
+            .script
+            .iter()
+            .map(|line| format!("> {}\n", line))
+            .collect();
+        format!("{}{}", script, self.computer.computer().save())
+    }
+    pub fn load(&mut self, text: &str) -> Result<(), ComputerError> {
+        let script: Vec<String> = text
+            .lines()
+            .map_while(|line| line.strip_prefix("> "))
+            .map(|line| line.to_string())
+            .collect();
+        let snapshot: Snapshot = text
+            .lines()
+            .skip(script.len())
+            .collect::<Vec<&str>>()
+            .join("\n")
+            .parse()?;
+        self.computer.computer_mut().restore(&snapshot);
+        self.script = script;
+        Ok(())
+    }
+}
+
+const HELP: &str = "\
+anything that doesn't start with : ! or @ goes to the program as a line of input
+commands:
+  :history             list the commands sent so far
+  !<n>                 send command n from the history again
+  :macro <name> <a; b> define a macro that sends commands a and b
+  :macro               list macros
+  @<name>              run a macro
+  :save <file>         save the game and its history
+  :load <file>         load a saved game
+  :export <file>       write the history as a script, one command per line
+  :replay <file>       send every command in a script
+  :help                show this
+  :quit                exit";
+
+fn send<W: Write>(session: &mut Session, line: &str, output: &mut W) -> io::Result<()> {
+    match session.command(line) {
+        Ok(reply) => {
+            write!(output, "{}", reply.text)?;
+            for value in reply.values.iter() {
+                writeln!(output, "[{}]", value)?;
+            }
+            if reply.halted() {
+                writeln!(output, "halted")?;
+            }
+        }
+        Err(error) => writeln!(output, "error: {}", error)?,
+    }
+    Ok(())
+}
+
+fn send_all<W: Write>(session: &mut Session, lines: &[String], output: &mut W) -> io::Result<()> {
+    for line in lines.iter() {
+        writeln!(output, "> {}", line)?;
+        send(session, line, output)?;
+    }
+    Ok(())
+}
+
+fn execute<W: Write>(session: &mut Session, line: &str, output: &mut W) -> io::Result<bool> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(true);
+    }
+    if let Some(n) = line.strip_prefix('!') {
+        match n
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| session.script().get(n))
+        {
+            Some(command) => send_all(session, &[command.to_string()], output)?,
+            None => writeln!(output, "no command {} in the history", n)?,
+        }
+        return Ok(true);
+    }
+    if let Some(name) = line.strip_prefix('@') {
+        match session.macros().get(name).cloned() {
+            Some(commands) => send_all(session, &commands, output)?,
+            None => writeln!(output, "no macro {}", name)?,
+        }
+        return Ok(true);
+    }
+    let meta = match line.strip_prefix(':') {
+        Some(meta) => meta,
+        None => {
+            send(session, line, output)?;
+            return Ok(true);
+        }
+    };
+    let (command, arg) = match meta.find(' ') {
+        Some(i) => (&meta[..i], meta[i + 1..].trim()),
+        None => (meta, ""),
+    };
+    match (command, arg) {
+        ("history", "") => {
+            for (i, command) in session.script().iter().enumerate() {
+                writeln!(output, "{:4} {}", i, command)?;
+            }
+        }
+        ("macro", "") => {
+            for (name, commands) in session.macros().iter() {
+                writeln!(output, "@{} {}", name, commands.join("; "))?;
+            }
+        }
+        ("macro", definition) => match definition.find(' ') {
+            Some(i) => {
+                let commands = definition[i + 1..]
+                    .split(';')
+                    .map(|command| command.trim().to_string())
+                    .filter(|command| !command.is_empty())
+                    .collect();
+                session.define(&definition[..i], commands);
+            }
+            None => writeln!(output, "usage: :macro <name> <command; command ...>")?,
+        },
+        ("save", file) if !file.is_empty() => fs::write(file, session.save())?,
+        ("export", file) if !file.is_empty() => fs::write(file, session.export())?,
+        ("load", file) if !file.is_empty() => match session.load(&fs::read_to_string(file)?) {
+            Ok(()) => writeln!(output, "loaded {} commands", session.script().len())?,
+            Err(error) => writeln!(output, "error: {}", error)?,
+        },
+        ("replay", file) if !file.is_empty() => {
+            let script = fs::read_to_string(file)?;
+            let commands: Vec<String> = script.lines().map(|line| line.to_string()).collect();
+            send_all(session, &commands, output)?;
+        }
+        ("help", "") => writeln!(output, "{}", HELP)?,
+        ("quit", "") => return Ok(false),
+        _ => writeln!(output, "unknown command: {} (try :help)", line)?,
+    }
+    Ok(true)
+}
+
+// file errors are reported without ending the game
+pub fn repl<R: BufRead, W: Write>(
+    session: &mut Session,
+    input: R,
+    output: &mut W,
+) -> io::Result<()> {
+    match session.start() {
+        Ok(reply) => write!(output, "{}", reply.text)?,
+        Err(error) => writeln!(output, "error: {}", error)?,
+    }
+    for line in input.lines() {
+        match execute(session, &line?, output) {
+            Ok(true) => (),
+            Ok(false) => break,
+            Err(error) => writeln!(output, "error: {}", error)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::Computer;
+    use std::env;
+
+    fn day_25() -> Session {
+        Session::new(AsciiComputer::new(&Computer::load_data(25).unwrap()))
+    }
+
+    fn play(session: &mut Session, input: &str) -> String {
+        let mut output = Vec::new();
+        repl(session, input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_history_and_macros() {
+        let mut session = day_25();
+        let output = play(
+            &mut session,
+            ":macro loop north; south\n@loop\n!0\n:history\n:macro\n@nowhere\n:quit\nnorth\n",
+        );
+        assert!(output.starts_with("\n\n\n== Hull Breach =="));
+        assert!(output.contains("> north\n"));
+        assert!(output.contains("   0 north\n   1 south\n   2 north\n"));
+        assert!(output.contains("@loop north; south\n"));
+        assert!(output.contains("no macro nowhere\n"));
+        // nothing after :quit gets sent
+        assert_eq!(session.script(), ["north", "south", "north"]);
+    }
+
+    #[test]
+    fn test_save_load_and_export() {
+        let dir = env::temp_dir();
+        let save = dir.join(format!("aoc_2019_play_{}.save", std::process::id()));
+        let script = dir.join(format!("aoc_2019_play_{}.txt", std::process::id()));
+        let mut session = day_25();
+        play(
+            &mut session,
+            &format!(
+                "south\n:save {}\nnorth\nnorth\n:export {}\n",
+                save.display(),
+                script.display()
+            ),
+        );
+        assert_eq!(
+            fs::read_to_string(&script).unwrap(),
+            "south\nnorth\nnorth\n"
+        );
+
+        // loading goes back to just after the first command
+        let mut loaded = Session::new(AsciiComputer::new(&[99]));
+        let output = play(&mut loaded, &format!(":load {}\ninv\n", save.display()));
+        assert!(output.contains("loaded 1 commands\n"));
+        assert_eq!(loaded.script(), ["south", "inv"]);
+
+        // and replaying the script ends up where the session did
+        let mut replayed = day_25();
+        play(&mut replayed, &format!(":replay {}\n", script.display()));
+        assert_eq!(replayed.save(), session.save());
+        fs::remove_file(save).unwrap();
+        fs::remove_file(script).unwrap();
+        assert!(play(&mut replayed, ":load /nonexistent\n").contains("error: "));
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+use super::disasm::{disassemble, is_jump, Line};
+use super::{Instruction, OpCode};
+
+const HOT_SPOTS: usize = 10;
+
+// a jump back to an earlier address, with how often it was taken
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct HotLoop {
+    pub start: i64,
+    pub end: i64,
+    pub iterations: u64,
+    // instructions executed between start and end, over every iteration
+    pub instructions: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    coverage: bool,
+    executions: BTreeMap<i64, u64>,
+    opcodes: HashMap<OpCode, u64>,
+    back_edges: BTreeMap<(i64, i64), u64>,
+    code: BTreeSet<i64>,
+    reads: BTreeSet<i64>,
+    writes: BTreeSet<i64>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler::default()
+    }
+    // also keeps track of every cell read or written as data, which costs a set insert per
+    // memory access
+    pub fn with_coverage(mut self) -> Self {
+        self.coverage = true;
+        self
+    }
+    pub(super) fn coverage(&self) -> bool {
+        self.coverage
+    }
+    // called by the computer after each executed instruction with where execution went next
+    pub(super) fn record(&mut self, address: i64, instruction: &Instruction, next: i64) {
+        *self.executions.entry(address).or_insert(0) += 1;
+        *self.opcodes.entry(instruction.opcode()).or_insert(0) += 1;
+        let len = instruction.opcode().parameter_count() as i64 + 1;
+        if is_jump(instruction.opcode()) && next <= address {
+            *self.back_edges.entry((address, next)).or_insert(0) += 1;
+        }
+        if self.coverage {
+            self.code.extend(address..address + len);
+        }
+    }
+    pub(super) fn read(&mut self, address: i64) {
+        self.reads.insert(address);
+    }
+    pub(super) fn write(&mut self, address: i64) {
+        self.writes.insert(address);
+    }
+    pub fn executions(&self, address: i64) -> u64 {
+        *self.executions.get(&address).unwrap_or(&0)
+    }
+    pub fn opcode_executions(&self, opcode: OpCode) -> u64 {
+        *self.opcodes.get(&opcode).unwrap_or(&0)
+    }
+    // addresses of every instruction executed at least once
+    pub fn executed(&self) -> impl Iterator<Item = i64> + '_ {
+        self.executions.keys().cloned()
+    }
+    pub fn is_code(&self, address: i64) -> bool {
+        self.code.contains(&address)
+    }
+    pub fn is_read(&self, address: i64) -> bool {
+        self.reads.contains(&address)
+    }
+    pub fn is_written(&self, address: i64) -> bool {
+        self.writes.contains(&address)
+    }
+    // loops ordered by the instructions spent in them, most first
+    pub fn hot_loops(&self) -> Vec<HotLoop> {
+        let mut loops: Vec<HotLoop> = self
+            .back_edges
+            .iter()
+            .map(|(&(end, start), &iterations)| HotLoop {
+                start,
+                end,
+                iterations,
+                instructions: self.executions.range(start..=end).map(|(_, n)| n).sum(),
+            })
+            .collect();
+        loops.sort_by_key(|hot| (std::cmp::Reverse(hot.instructions), hot.start));
+        loops
+    }
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let total: u64 = self.executions.values().sum();
+        writeln!(report, "{} instructions executed", total).unwrap();
+
+        writeln!(report, "\nby opcode:").unwrap();
+        let mut opcodes: Vec<(&OpCode, &u64)> = self.opcodes.iter().collect();
+        opcodes.sort_by_key(|&(opcode, &count)| (std::cmp::Reverse(count), opcode.code()));
+        for (opcode, count) in opcodes {
+            writeln!(report, "{:>12} {}", count, opcode.mnemonic()).unwrap();
+        }
+
+        writeln!(report, "\nhottest addresses:").unwrap();
+        let mut addresses: Vec<(&i64, &u64)> = self.executions.iter().collect();
+        addresses.sort_by_key(|&(&address, &count)| (std::cmp::Reverse(count), address));
+        for (address, count) in addresses.into_iter().take(HOT_SPOTS) {
+            writeln!(report, "{:>12} {}", count, address).unwrap();
+        }
+
+        writeln!(report, "\nhot loops:").unwrap();
+        for hot in self.hot_loops().into_iter().take(HOT_SPOTS) {
+            writeln!(
+                report,
+                "{:>12} {}..={}, {} iterations",
+                hot.instructions, hot.start, hot.end, hot.iterations
+            )
+            .unwrap();
+        }
+
+        if self.coverage {
+            let both = self
+                .code
+                .iter()
+                .filter(|address| self.reads.contains(address) || self.writes.contains(address))
+                .count();
+            writeln!(report, "\ncoverage:").unwrap();
+            writeln!(report, "{:>12} cells executed", self.code.len()).unwrap();
+            writeln!(report, "{:>12} cells read as data", self.reads.len()).unwrap();
+            writeln!(report, "{:>12} cells written", self.writes.len()).unwrap();
+            writeln!(report, "{:>12} cells both executed and used as data", both).unwrap();
+        }
+        report
+    }
+    // the disassembly with execution counts in front of every instruction, and with coverage
+    // a column marking lines that were executed (x), read (r) and written (w)
+    pub fn annotate(&self, program: &[i64]) -> String {
+        let disassembly = disassemble(program);
+        let mut listing = String::new();
+        for line in disassembly.lines.iter() {
+            let (start, len) = match line {
+                Line::Code {
+                    address, operands, ..
+                } => (*address, operands.len() as i64 + 1),
+                Line::Data { address, values } => (*address, values.len() as i64),
+            };
+            let count = match self.executions.get(&start) {
+                Some(count) if matches!(line, Line::Code { .. }) => count.to_string(),
+                _ => "-".to_string(),
+            };
+            write!(listing, "{:>10} ", count).unwrap();
+            if self.coverage {
+                let cells = start..start + len;
+                let mark = |set: &BTreeSet<i64>, c: char| {
+                    if set.range(cells.clone()).next().is_some() {
+                        c
+                    } else {
+                        '.'
+                    }
+                };
+                write!(
+                    listing,
+                    "{}{}{} ",
+                    mark(&self.code, 'x'),
+                    mark(&self.reads, 'r'),
+                    mark(&self.writes, 'w')
+                )
+                .unwrap();
+            }
+            writeln!(listing, "{}", line).unwrap();
+        }
+        listing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+    use crate::intcode::Computer;
+
+    // counts x down to zero, outputting each value
+    fn program() -> Vec<i64> {
+        assemble(
+            "
+                in x
+            loop:
+                out x
+                add x, #-1, x
+                jnz x, #loop
+                hlt
+            x: db 0
+            ",
+        )
+        .unwrap()
+    }
+
+    fn profile(input: i64) -> Profiler {
+        let mut computer = Computer::new(&program(), &[input]);
+        computer.set_profiler(Profiler::new().with_coverage());
+        computer.by_ref().for_each(drop);
+        computer.take_profiler().unwrap()
+    }
+
+    #[test]
+    fn test_counts() {
+        let profiler = profile(3);
+        assert_eq!(
+            profiler.executed().collect::<Vec<i64>>(),
+            vec![0, 2, 4, 8, 11]
+        );
+        assert_eq!(profiler.executions(2), 3);
+        assert_eq!(profiler.executions(11), 1);
+        assert_eq!(profiler.opcode_executions(OpCode::Add), 3);
+        assert_eq!(
+            profiler.hot_loops(),
+            vec![HotLoop {
+                start: 2,
+                end: 8,
+                iterations: 2,
+                instructions: 9,
+            }]
+        );
+        let report = profiler.report();
+        assert!(report.starts_with("11 instructions executed\n"));
+        assert!(report.contains("           3 out\n"));
+        assert!(report.contains("           9 2..=8, 2 iterations\n"));
+        assert!(report.contains("          12 cells executed\n"));
+    }
+
+    #[test]
+    fn test_coverage() {
+        let profiler = profile(3);
+        assert!((0..12).all(|address| profiler.is_code(address)));
+        assert!(!profiler.is_code(12));
+        assert!(profiler.is_read(12) && profiler.is_written(12));
+        assert!(!profiler.is_read(0) && !profiler.is_written(0));
+
+        let listing = profiler.annotate(&program());
+        let lines: Vec<&str> = listing.lines().collect();
+        assert!(lines[0].starts_with("         1 x..     in 12 "));
+        assert!(lines[1].starts_with("         3 x..     out 12 "));
+        assert!(lines
+            .last()
+            .unwrap()
+            .starts_with("         - .rw     db 0 "));
+    }
+
+    #[test]
+    fn test_without_coverage() {
+        let mut computer = Computer::new(&program(), &[2]);
+        computer.set_profiler(Profiler::new());
+        computer.by_ref().for_each(drop);
+        let profiler = computer.take_profiler().unwrap();
+        assert_eq!(profiler.executions(4), 2);
+        assert!(!profiler.is_code(0) && !profiler.is_read(12));
+        assert!(!profiler.report().contains("coverage"));
+        assert!(profiler
+            .annotate(&program())
+            .starts_with("         1     in 12 "));
+    }
+}
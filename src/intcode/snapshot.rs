@@ -0,0 +1,238 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use super::{ComputerError, DenseMemory, Memory};
+
+const HEADER: &str = "intcode-snapshot 1";
+
+// keeps the computer's own memory, so saving and restoring is just a clone
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot<M: Memory = DenseMemory> {
+    pub memory: M,
+    pub input_queue: VecDeque<i64>,
+    pub yield_on_empty: bool,
+    pub default_input: i64,
+    pub pos: i64,
+    pub relative_base: i64,
+}
+
+impl<M: Memory> PartialEq for Snapshot<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.memory.cells() == other.memory.cells()
+            && self.input_queue == other.input_queue
+            && self.yield_on_empty == other.yield_on_empty
+            && self.default_input == other.default_input
+            && self.pos == other.pos
+            && self.relative_base == other.relative_base
+    }
+}
+
+impl<M: Memory> Eq for Snapshot<M> {}
+
+pub(super) fn join(values: impl Iterator<Item = i64>) -> String {
+    values
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+// one line per field, memory written as runs of consecutive addresses so sparse far writes
+// don't blow up the file
+impl<M: Memory> fmt::Display for Snapshot<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "pos {}", self.pos)?;
+        writeln!(f, "relative_base {}", self.relative_base)?;
+        writeln!(f, "default_input {}", self.default_input)?;
+        writeln!(f, "yield_on_empty {}", self.yield_on_empty as i64)?;
+        writeln!(f, "input {}", join(self.input_queue.iter().cloned()))?;
+        let cells = self.memory.cells();
+        let mut start = 0;
+        while start < cells.len() {
+            let mut end = start + 1;
+            while end < cells.len() && cells[end].0 == cells[end - 1].0 + 1 {
+                end += 1;
+            }
+            let values = cells[start..end].iter().map(|&(_, value)| value);
+            writeln!(f, "memory {} {}", cells[start].0, join(values))?;
+            start = end;
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn parse_error(line: usize, column: usize, token: &str) -> ComputerError {
+    ComputerError::Parse {
+        line,
+        column,
+        token: token.to_string(),
+    }
+}
+
+pub(super) fn parse_number(text: &str, line: usize, column: usize) -> Result<i64, ComputerError> {
+    text.parse().map_err(|_| parse_error(line, column, text))
+}
+
+pub(super) fn parse_list(
+    text: &str,
+    line: usize,
+    column: usize,
+) -> Result<Vec<i64>, ComputerError> {
+    if text.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut column = column;
+    let mut result = Vec::new();
+    for value in text.split(',') {
+        result.push(parse_number(value, line, column)?);
+        column += value.len() + 1;
+    }
+    Ok(result)
+}
+
+impl<M: Memory> FromStr for Snapshot<M> {
+    type Err = ComputerError;
+
+    fn from_str(text: &str) -> Result<Self, ComputerError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+        match lines.next() {
+            Some((_, HEADER)) => (),
+            Some((_, header)) => return Err(parse_error(1, 1, header)),
+            None => return Err(parse_error(1, 1, "")),
+        }
+        let mut snapshot: Snapshot<M> = Snapshot::default();
+        for (line, text) in lines {
+            let (key, value) = match text.find(' ') {
+                Some(i) => (&text[..i], &text[i + 1..]),
+                None => (text, ""),
+            };
+            let column = key.len() + 2;
+            match key {
+                "pos" => snapshot.pos = parse_number(value, line, column)?,
+                "relative_base" => snapshot.relative_base = parse_number(value, line, column)?,
+                "default_input" => snapshot.default_input = parse_number(value, line, column)?,
+                "yield_on_empty" => {
+                    snapshot.yield_on_empty = parse_number(value, line, column)? != 0
+                }
+                "input" => snapshot.input_queue = parse_list(value, line, column)?.into(),
+                "memory" => {
+                    let (start, values) = match value.find(' ') {
+                        Some(i) => (&value[..i], &value[i + 1..]),
+                        None => return Err(parse_error(line, column, value)),
+                    };
+                    let start_address = parse_number(start, line, column)?;
+                    if start_address < 0 {
+                        return Err(parse_error(line, column, start));
+                    }
+                    let values = parse_list(values, line, column + start.len() + 1)?;
+                    for (i, value) in values.into_iter().enumerate() {
+                        // a run that goes past the last address is as bad as one before the first
+                        let address = start_address
+                            .checked_add(i as i64)
+                            .ok_or_else(|| parse_error(line, column, start))?;
+                        snapshot.memory.set(address, value);
+                    }
+                }
+                "" => (),
+                _ => return Err(parse_error(line, 1, key)),
+            }
+        }
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+    use crate::intcode::{Computer, Memory, State};
+
+    fn program() -> Vec<i64> {
+        assemble(
+            "
+            loop:
+                in x
+                add total, x, total
+                out total
+                jnz #1, #loop
+            x:     db 0
+            total: db 0
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_save_and_restore() {
+        let mut computer = Computer::new(&program(), &[1, 2]);
+        computer.yield_on_empty = true;
+        assert_eq!(computer.run(), Ok(State::Output(1)));
+        let snapshot = computer.save();
+        assert_eq!(computer.run(), Ok(State::Output(3)));
+        computer.add_input(&[10]);
+        assert_eq!(computer.run(), Ok(State::Output(13)));
+
+        computer.restore(&snapshot);
+        assert_eq!(computer.input_queue, vec![2]);
+        assert_eq!(computer.run(), Ok(State::Output(3)));
+        assert_eq!(computer.run(), Ok(State::NeedsInput));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut computer = Computer::new(&program(), &[5, 6, 7]);
+        computer.set_default_input(-1);
+        computer.program.set(1_000_000, 42);
+        assert_eq!(computer.run(), Ok(State::Output(5)));
+        let text = computer.save().to_string();
+        assert_eq!(
+            text,
+            concat!(
+                "intcode-snapshot 1\n",
+                "pos 8\n",
+                "relative_base 0\n",
+                "default_input -1\n",
+                "yield_on_empty 0\n",
+                "input 6,7\n",
+                "memory 0 3,11,1,12,11,12,4,12,1105,1,0,5,5\n",
+                "memory 1000000 42\n",
+            )
+        );
+        let snapshot: Snapshot = text.parse().unwrap();
+        assert_eq!(snapshot, computer.save());
+
+        let mut resumed: Computer = Computer::from_snapshot(&snapshot);
+        let mut original = computer;
+        for _ in 0..3 {
+            assert_eq!(resumed.run(), original.run());
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("pos 1".parse::<Snapshot>(), Err(parse_error(1, 1, "pos 1")));
+        assert_eq!(
+            format!("{}\ninput 1,x,3", HEADER).parse::<Snapshot>(),
+            Err(parse_error(2, 9, "x"))
+        );
+        assert_eq!(
+            format!("{}\nmemory 0 1,2\nspeed 4", HEADER).parse::<Snapshot>(),
+            Err(parse_error(3, 1, "speed"))
+        );
+        // restoring memory below address 0 would break the computer
+        assert_eq!(
+            format!("{}\nmemory -3 99", HEADER).parse::<Snapshot>(),
+            Err(parse_error(2, 8, "-3"))
+        );
+        assert_eq!(
+            format!(
+                "{}
+memory 9223372036854775807 1,2",
+                HEADER
+            )
+            .parse::<Snapshot>(),
+            Err(parse_error(2, 8, "9223372036854775807"))
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use std::fmt;
+
+// counts since the computer was created or last reset
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Stats {
+    pub instructions: u64,
+    pub inputs: u64,
+    pub outputs: u64,
+    // highest address read or written, including instruction fetches
+    pub max_address: i64,
+}
+
+impl Stats {
+    pub(super) fn touch(&mut self, address: i64) {
+        self.max_address = self.max_address.max(address);
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} instructions, {} inputs, {} outputs, max address {}",
+            self.instructions, self.inputs, self.outputs, self.max_address
+        )
+    }
+}
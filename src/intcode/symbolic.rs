@@ -0,0 +1,623 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use super::{Computer, ComputerError, Instruction, OpCode, ParameterMode, State};
+
+const BUDGET: u64 = 10_000_000;
+// how many times solve moves the concrete run to its last answer before giving up
+const ATTEMPTS: usize = 8;
+
+// a value the program doesn't know until it runs
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Var {
+    // the initial contents of a memory cell
+    Cell(i64),
+    // the nth value read by an input instruction
+    Input(usize),
+}
+
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Var::Cell(address) => write!(f, "[{}]", address),
+            Var::Input(n) => write!(f, "in{}", n),
+        }
+    }
+}
+
+pub type Assignment = BTreeMap<Var, i64>;
+
+// constant + sum of coefficient * var, wrapping on overflow like the computer does
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Linear {
+    pub constant: i64,
+    pub terms: BTreeMap<Var, i64>,
+}
+
+impl Linear {
+    pub fn constant(constant: i64) -> Self {
+        Linear {
+            constant,
+            terms: BTreeMap::new(),
+        }
+    }
+    pub fn var(var: Var) -> Self {
+        Linear {
+            constant: 0,
+            terms: vec![(var, 1)].into_iter().collect(),
+        }
+    }
+    pub fn is_constant(&self) -> bool {
+        self.terms.is_empty()
+    }
+    pub fn evaluate(&self, assignment: &Assignment) -> i64 {
+        self.terms
+            .iter()
+            .map(|(var, coefficient)| coefficient.wrapping_mul(*assignment.get(var).unwrap_or(&0)))
+            .fold(self.constant, i64::wrapping_add)
+    }
+    fn add(&self, other: &Linear) -> Linear {
+        let mut result = self.clone();
+        result.constant = result.constant.wrapping_add(other.constant);
+        for (&var, &coefficient) in other.terms.iter() {
+            let sum = result.terms.entry(var).or_insert(0);
+            *sum = sum.wrapping_add(coefficient);
+        }
+        result.terms.retain(|_, coefficient| *coefficient != 0);
+        result
+    }
+    fn scale(&self, factor: i64) -> Linear {
+        let mut result = Linear::constant(self.constant.wrapping_mul(factor));
+        result.terms = self
+            .terms
+            .iter()
+            .map(|(&var, &coefficient)| (var, coefficient.wrapping_mul(factor)))
+            .filter(|&(_, coefficient)| coefficient != 0)
+            .collect();
+        result
+    }
+}
+
+impl fmt::Display for Linear {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        if self.constant != 0 || self.terms.is_empty() {
+            parts.push(self.constant.to_string());
+        }
+        for (var, coefficient) in self.terms.iter() {
+            match coefficient {
+                1 => parts.push(var.to_string()),
+                _ => parts.push(format!("{}*{}", coefficient, var)),
+            }
+        }
+        write!(f, "{}", parts.join(" + "))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Value {
+    Linear(Linear),
+    // a product of two values that both depend on the variables
+    Nonlinear,
+}
+
+impl Value {
+    fn constant(&self) -> Option<i64> {
+        match self {
+            Value::Linear(linear) if linear.is_constant() => Some(linear.constant),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Linear(linear) => write!(f, "{}", linear),
+            Value::Nonlinear => write!(f, "nonlinear"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SymbolicError {
+    Computer(ComputerError),
+    // the value asked about isn't a linear function of the variables
+    Nonlinear,
+    // no answer found on one path held on the path it led the program down
+    Diverged,
+}
+
+impl fmt::Display for SymbolicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SymbolicError::Computer(error) => write!(f, "{}", error),
+            SymbolicError::Nonlinear => write!(f, "value depends non-linearly on the inputs"),
+            SymbolicError::Diverged => write!(f, "no solution stays on the path it was found on"),
+        }
+    }
+}
+
+impl Error for SymbolicError {}
+
+// a concrete run with every cell that depends on the variables shadowed by its expression
+#[derive(Debug)]
+pub struct Execution {
+    pub state: State,
+    computer: Computer,
+    memory: HashMap<i64, Value>,
+    pub outputs: Vec<Value>,
+    // instructions that multiplied two values that both depend on the variables
+    pub nonlinear: BTreeSet<i64>,
+    // instructions where a value that depends on the variables picked a branch or an address,
+    // so the expressions only hold for assignments that make the same choice
+    pub decisions: BTreeSet<i64>,
+}
+
+impl Execution {
+    pub fn value(&self, address: i64) -> Value {
+        match self.memory.get(&address) {
+            Some(value) => value.clone(),
+            None if address < 0 => Value::Linear(Linear::constant(0)),
+            None => Value::Linear(Linear::constant(self.computer.peek(address))),
+        }
+    }
+    // what the concrete run actually left at address
+    pub fn concrete(&self, address: i64) -> i64 {
+        self.computer.peek(address)
+    }
+    fn decide(&mut self, pos: i64, value: &Value) {
+        if value.constant().is_none() {
+            self.decisions.insert(pos);
+        }
+    }
+    fn address(&mut self, pos: i64, offset: i64, mode: ParameterMode) -> i64 {
+        let cell = pos + offset;
+        let raw = self.value(cell);
+        self.decide(pos, &raw);
+        match mode {
+            ParameterMode::Relative => self
+                .computer
+                .relative_base()
+                .wrapping_add(self.concrete(cell)),
+            _ => self.concrete(cell),
+        }
+    }
+    fn operand(&mut self, pos: i64, offset: i64, mode: ParameterMode) -> Value {
+        match mode {
+            ParameterMode::Immediate => self.value(pos + offset),
+            _ => {
+                let address = self.address(pos, offset, mode);
+                self.value(address)
+            }
+        }
+    }
+    // the cell the instruction at pos is about to write and what it will hold, worked out
+    // before the computer runs it. None means whatever concrete value the computer writes
+    fn effect(&mut self, input: Option<Var>) -> Option<(i64, Option<Value>)> {
+        let pos = self.computer.pos();
+        let code = self.value(pos);
+        self.decide(pos, &code);
+        let instruction = Instruction::decode(self.concrete(pos), pos).ok()?;
+        let [a, b, c] = instruction.modes();
+        match instruction.opcode() {
+            OpCode::Add | OpCode::Multiply | OpCode::LessThan | OpCode::Equals => {
+                let x = self.operand(pos, 1, a);
+                let y = self.operand(pos, 2, b);
+                let value = match (instruction.opcode(), &x, &y) {
+                    (OpCode::Add, Value::Linear(x), Value::Linear(y)) => Value::Linear(x.add(y)),
+                    (OpCode::Multiply, _, _)
+                        if x.constant() == Some(0) || y.constant() == Some(0) =>
+                    {
+                        Value::Linear(Linear::constant(0))
+                    }
+                    (OpCode::Multiply, Value::Linear(x), Value::Linear(y)) if y.is_constant() => {
+                        Value::Linear(x.scale(y.constant))
+                    }
+                    (OpCode::Multiply, Value::Linear(x), Value::Linear(y)) if x.is_constant() => {
+                        Value::Linear(y.scale(x.constant))
+                    }
+                    (OpCode::Add, _, _) | (OpCode::Multiply, _, _) => {
+                        if x != Value::Nonlinear && y != Value::Nonlinear {
+                            self.nonlinear.insert(pos);
+                        }
+                        Value::Nonlinear
+                    }
+                    // comparisons only keep their concrete outcome
+                    _ => {
+                        self.decide(pos, &x);
+                        self.decide(pos, &y);
+                        return Some((self.address(pos, 3, c), None));
+                    }
+                };
+                Some((self.address(pos, 3, c), Some(value)))
+            }
+            OpCode::JumpIfTrue | OpCode::JumpIfFalse => {
+                let condition = self.operand(pos, 1, a);
+                let target = self.operand(pos, 2, b);
+                self.decide(pos, &condition);
+                self.decide(pos, &target);
+                None
+            }
+            OpCode::Input => {
+                let address = self.address(pos, 1, a);
+                input.map(|var| (address, Some(Value::Linear(Linear::var(var)))))
+            }
+            OpCode::Output => {
+                let value = self.operand(pos, 1, a);
+                self.outputs.push(value);
+                None
+            }
+            OpCode::RelativeBaseOffset => {
+                let offset = self.operand(pos, 1, a);
+                self.decide(pos, &offset);
+                None
+            }
+            OpCode::Stop => None,
+        }
+    }
+}
+
+// concolic execution of a program where some cells and inputs are variables. every run is a
+// real run of the program on a concrete assignment that also tracks which cells are linear
+// functions of the variables
+pub struct Symbolic {
+    program: Vec<i64>,
+    domains: BTreeMap<Var, RangeInclusive<i64>>,
+    inputs: usize,
+    budget: u64,
+}
+
+impl Symbolic {
+    pub fn new(program: &[i64]) -> Self {
+        Symbolic {
+            program: program.to_vec(),
+            domains: BTreeMap::new(),
+            inputs: 0,
+            budget: BUDGET,
+        }
+    }
+    pub fn with_cell(mut self, address: i64, domain: RangeInclusive<i64>) -> Self {
+        self.domains.insert(Var::Cell(address), domain);
+        self
+    }
+    // makes the next value the program reads a variable
+    pub fn with_input(mut self, domain: RangeInclusive<i64>) -> Self {
+        self.domains.insert(Var::Input(self.inputs), domain);
+        self.inputs += 1;
+        self
+    }
+    pub fn with_budget(mut self, instructions: u64) -> Self {
+        self.budget = instructions;
+        self
+    }
+    // the assignment every variable starts its domain with
+    pub fn lowest(&self) -> Assignment {
+        self.domains
+            .iter()
+            .map(|(&var, domain)| (var, *domain.start()))
+            .collect()
+    }
+    // runs until the program halts or wants more input than there are input variables
+    pub fn run(&self, assignment: &Assignment) -> Result<Execution, SymbolicError> {
+        let mut program = self.program.clone();
+        for (&var, &value) in assignment.iter() {
+            if let Var::Cell(address) = var {
+                if address < 0 {
+                    let error = ComputerError::NegativeAddress { address };
+                    return Err(SymbolicError::Computer(error));
+                }
+                // cells past the end of the program start out as 0 like the rest of memory
+                if address as usize >= program.len() {
+                    program.resize(address as usize + 1, 0);
+                }
+                program[address as usize] = value;
+            }
+        }
+        let inputs: Vec<i64> = (0..self.inputs)
+            .map(|n| *assignment.get(&Var::Input(n)).unwrap_or(&0))
+            .collect();
+        let mut computer = Computer::new(&program, &inputs);
+        computer.yield_on_empty = true;
+        computer.set_budget(Some(self.budget));
+        let mut execution = Execution {
+            state: State::NeedsInput,
+            computer,
+            memory: self
+                .domains
+                .keys()
+                .filter_map(|&var| match var {
+                    Var::Cell(address) => Some((address, Value::Linear(Linear::var(var)))),
+                    Var::Input(_) => None,
+                })
+                .collect(),
+            outputs: Vec::new(),
+            nonlinear: BTreeSet::new(),
+            decisions: BTreeSet::new(),
+        };
+        let mut read = 0;
+        loop {
+            let input = Some(Var::Input(read)).filter(|_| read < self.inputs);
+            let outputs = execution.outputs.len();
+            let effect = execution.effect(input);
+            let state = execution.computer.step().map_err(SymbolicError::Computer)?;
+            match state {
+                Some(State::NeedsInput) | Some(State::Halted) => {
+                    execution.outputs.truncate(outputs);
+                    execution.state = state.unwrap();
+                    return Ok(execution);
+                }
+                _ => (),
+            }
+            match effect {
+                Some((address, Some(value))) if value.constant().is_none() => {
+                    execution.memory.insert(address, value);
+                }
+                Some((address, value)) => {
+                    let constant = value.and_then(|value| value.constant());
+                    debug_assert!(constant.is_none_or(|c| c == execution.concrete(address)));
+                    execution.memory.remove(&address);
+                }
+                None => (),
+            }
+            if input.is_some() && execution.computer.stats().inputs > read as u64 {
+                read += 1;
+            }
+        }
+    }
+    // finds an assignment within the domains that leaves target at address once the program
+    // stops, or None if the linear expression for it can't reach the target
+    pub fn solve(&self, address: i64, target: i64) -> Result<Option<Assignment>, SymbolicError> {
+        let mut assignment = self.lowest();
+        for _ in 0..ATTEMPTS {
+            let execution = self.run(&assignment)?;
+            if execution.concrete(address) == target {
+                return Ok(Some(assignment));
+            }
+            let linear = match execution.value(address) {
+                Value::Linear(linear) => linear,
+                Value::Nonlinear => return Err(SymbolicError::Nonlinear),
+            };
+            match self.solve_linear(&linear, target) {
+                // without any decisions the expression holds everywhere and the answer is exact
+                None if execution.decisions.is_empty() => return Ok(None),
+                None => return Err(SymbolicError::Diverged),
+                Some(next) if next == assignment => return Err(SymbolicError::Diverged),
+                Some(next) => assignment = next,
+            }
+        }
+        Err(SymbolicError::Diverged)
+    }
+    // enumerates every variable but the last and solves for that one directly
+    fn solve_linear(&self, linear: &Linear, target: i64) -> Option<Assignment> {
+        let mut assignment = self.lowest();
+        let (&last, &coefficient) = match linear.terms.iter().next_back() {
+            Some(term) => term,
+            None => return Some(assignment).filter(|_| linear.constant == target),
+        };
+        let others: Vec<Var> = linear
+            .terms
+            .keys()
+            .cloned()
+            .filter(|&v| v != last)
+            .collect();
+        let mut values: Vec<i64> = others
+            .iter()
+            .map(|var| *self.domains[var].start())
+            .collect();
+        loop {
+            for (var, &value) in others.iter().zip(values.iter()) {
+                assignment.insert(*var, value);
+            }
+            // wrapped coefficients can make this miss or overshoot, which the next run catches
+            let rest = target
+                .wrapping_sub(linear.evaluate(&assignment))
+                .wrapping_add(coefficient.wrapping_mul(assignment[&last]));
+            let value = Some(rest)
+                .filter(|&rest| rest.checked_rem(coefficient) == Some(0))
+                .and_then(|rest| rest.checked_div(coefficient));
+            if let Some(value) = value.filter(|value| self.domains[&last].contains(value)) {
+                assignment.insert(last, value);
+                return Some(assignment);
+            }
+            // next combination, like counting with each digit in its own domain
+            let mut i = 0;
+            loop {
+                if i == values.len() {
+                    return None;
+                }
+                if values[i] < *self.domains[&others[i]].end() {
+                    values[i] += 1;
+                    break;
+                }
+                values[i] = *self.domains[&others[i]].start();
+                i += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    fn day_2() -> Symbolic {
+        Symbolic::new(&Computer::load_data(2).unwrap())
+            .with_cell(1, 0..=99)
+            .with_cell(2, 0..=99)
+    }
+
+    #[test]
+    fn test_day_2_is_linear() {
+        let execution = day_2().run(&day_2().lowest()).unwrap();
+        assert_eq!(execution.state, State::Halted);
+        assert!(execution.nonlinear.is_empty());
+        // the first instruction uses the noun and verb as addresses before they are read as
+        // values, but overwrites what it computed straight away
+        assert_eq!(execution.decisions, vec![0].into_iter().collect());
+        match execution.value(0) {
+            Value::Linear(linear) => {
+                assert_eq!(linear.terms.len(), 2);
+                assert_eq!(linear.terms[&Var::Cell(2)], 1);
+                let assignment = vec![(Var::Cell(1), 12), (Var::Cell(2), 2)]
+                    .into_iter()
+                    .collect();
+                assert_eq!(linear.evaluate(&assignment), 4_090_689);
+            }
+            Value::Nonlinear => panic!("day 2 should be linear"),
+        }
+    }
+
+    #[test]
+    fn test_solve_day_2() {
+        let assignment = day_2().solve(0, 19_690_720).unwrap().unwrap();
+        assert_eq!(assignment[&Var::Cell(1)], 77);
+        assert_eq!(assignment[&Var::Cell(2)], 33);
+        // out of reach, but the address decision at 0 means that can't be proven
+        assert_eq!(day_2().solve(0, 1), Err(SymbolicError::Diverged));
+    }
+
+    #[test]
+    fn test_inputs() {
+        // out 3 * in0 - in1 + 5, then in0 * in1
+        let program = assemble(
+            "
+                in a
+                in b
+                mul a, #3, x
+                mul b, #-1, y
+                add x, y, x
+                add x, #5, x
+                out x
+                mul a, b, x
+                out x
+                hlt
+            a: db 0
+            b: db 0
+            x: db 0
+            y: db 0
+            ",
+        )
+        .unwrap();
+        let symbolic = Symbolic::new(&program)
+            .with_input(0..=10)
+            .with_input(0..=10);
+        let execution = symbolic.run(&symbolic.lowest()).unwrap();
+        assert_eq!(execution.outputs.len(), 2);
+        assert_eq!(execution.outputs[0].to_string(), "5 + 3*in0 + -1*in1");
+        assert_eq!(execution.outputs[1], Value::Nonlinear);
+        assert_eq!(execution.nonlinear, vec![22].into_iter().collect());
+        assert!(execution.decisions.is_empty());
+        assert_eq!(
+            symbolic.solve(program.len() as i64 - 2, 1),
+            Err(SymbolicError::Nonlinear)
+        );
+    }
+
+    #[test]
+    fn test_comparison_overwrites_variable() {
+        // in0 goes to a, then eq replaces it with whether it was 3, and out a + 10
+        let program = assemble(
+            "
+                in a
+                eq a, #3, a
+                add a, #10, b
+                out b
+                hlt
+            a: db 0
+            b: db 0
+            ",
+        )
+        .unwrap();
+        let symbolic = Symbolic::new(&program).with_input(0..=9);
+        let a = program.len() as i64 - 2;
+        let execution = symbolic.run(&symbolic.lowest()).unwrap();
+        assert_eq!(execution.value(a), Value::Linear(Linear::constant(0)));
+        assert_eq!(execution.outputs, vec![Value::Linear(Linear::constant(10))]);
+        assert_eq!(execution.decisions, vec![2].into_iter().collect());
+        // the comparison only keeps its concrete outcome, so 11 can't be solved for
+        assert!(symbolic.solve(a + 1, 10).unwrap().is_some());
+        assert_eq!(symbolic.solve(a + 1, 11), Err(SymbolicError::Diverged));
+    }
+
+    #[test]
+    fn test_cells_outside_the_program() {
+        // add [5], [6], [0] with both cells past the end
+        let program = vec![1, 5, 6, 0, 99];
+        let symbolic = Symbolic::new(&program).with_cell(6, 0..=9);
+        let execution = symbolic.run(&symbolic.lowest()).unwrap();
+        assert_eq!(execution.value(0).to_string(), "[6]");
+        assert_eq!(
+            Symbolic::new(&program).with_cell(-1, 0..=9).solve(0, 1),
+            Err(SymbolicError::Computer(ComputerError::NegativeAddress {
+                address: -1
+            }))
+        );
+    }
+
+    #[test]
+    fn test_overflow_wraps() {
+        // x * 2^62 doubled wraps around the same way it does in the computer
+        let program = assemble(
+            "
+                mul x, #4611686018427387904, y
+                add y, y, z
+                hlt
+            x: db 0
+            y: db 0
+            z: db 0
+            ",
+        )
+        .unwrap();
+        let z = program.len() as i64 - 1;
+        let symbolic = Symbolic::new(&program).with_cell(z - 2, 0..=3);
+        let assignment: Assignment = vec![(Var::Cell(z - 2), 1)].into_iter().collect();
+        let execution = symbolic.run(&assignment).unwrap();
+        assert_eq!(execution.concrete(z), i64::MIN);
+        match execution.value(z) {
+            Value::Linear(linear) => assert_eq!(linear.evaluate(&assignment), i64::MIN),
+            Value::Nonlinear => panic!("z should be linear"),
+        }
+        // a relative address that wraps fails the way it does in the computer
+        let program = assemble("arb #9223372036854775807\nout rb+1").unwrap();
+        assert_eq!(
+            Symbolic::new(&program).run(&Assignment::new()).err(),
+            Some(SymbolicError::Computer(ComputerError::NegativeAddress {
+                address: i64::MIN
+            }))
+        );
+    }
+
+    #[test]
+    fn test_branches() {
+        // doubles the input if it is below 5, otherwise adds 100
+        let program = assemble(
+            "
+                in a
+                lt a, #5, flag
+                jz flag, #big
+                mul a, #2, a
+                hlt
+            big:
+                add a, #100, a
+                hlt
+            a:    db 0
+            flag: db 0
+            ",
+        )
+        .unwrap();
+        let symbolic = Symbolic::new(&program).with_input(0..=200);
+        let a = program.len() as i64 - 2;
+        let execution = symbolic.run(&symbolic.lowest()).unwrap();
+        // the comparison decides the flag, so the jump on it is concrete
+        assert_eq!(execution.decisions, vec![2].into_iter().collect());
+        // the first run doubles, so it tries 75, which adds 100 instead and leads to 50
+        let assignment = symbolic.solve(a, 150).unwrap().unwrap();
+        assert_eq!(assignment[&Var::Input(0)], 50);
+        assert_eq!(symbolic.solve(a, 8).unwrap().unwrap()[&Var::Input(0)], 4);
+        assert_eq!(symbolic.solve(a, 7), Err(SymbolicError::Diverged));
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+use super::disasm::Operand;
+use super::{Instruction, OpCode};
+
+pub struct Tracer {
+    sink: Box<dyn Write>,
+    addresses: Option<Range<i64>>,
+    opcodes: Option<HashSet<OpCode>>,
+    pub(super) reads: Vec<i64>,
+    pub(super) writes: Vec<(i64, i64)>,
+}
+
+impl fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tracer")
+            .field("addresses", &self.addresses)
+            .field("opcodes", &self.opcodes)
+            .finish()
+    }
+}
+
+impl Tracer {
+    pub fn new<W: Write + 'static>(sink: W) -> Self {
+        Tracer {
+            sink: Box::new(sink),
+            addresses: None,
+            opcodes: None,
+            reads: Vec::new(),
+            writes: Vec::new(),
+        }
+    }
+    pub fn with_addresses(mut self, addresses: Range<i64>) -> Self {
+        self.addresses = Some(addresses);
+        self
+    }
+    pub fn with_opcodes(mut self, opcodes: &[OpCode]) -> Self {
+        self.opcodes = Some(opcodes.iter().cloned().collect());
+        self
+    }
+    fn accepts(&self, address: i64, opcode: OpCode) -> bool {
+        self.addresses
+            .as_ref()
+            .is_none_or(|range| range.contains(&address))
+            && self
+                .opcodes
+                .as_ref()
+                .is_none_or(|opcodes| opcodes.contains(&opcode))
+    }
+    // called by the computer after each executed instruction, with the raw parameters as they
+    // were before execution and the relative base if the instruction changed it
+    pub(super) fn record(
+        &mut self,
+        address: i64,
+        instruction: &Instruction,
+        params: &[i64],
+        relative_base: Option<i64>,
+    ) -> io::Result<()> {
+        let reads: Vec<i64> = self.reads.drain(..).collect();
+        let writes: Vec<(i64, i64)> = self.writes.drain(..).collect();
+        if !self.accepts(address, instruction.opcode()) {
+            return Ok(());
+        }
+        let operands: Vec<String> = params
+            .iter()
+            .zip(instruction.modes().iter())
+            .map(|(&value, &mode)| {
+                Operand {
+                    mode,
+                    value,
+                    label: false,
+                }
+                .to_string()
+            })
+            .collect();
+        let mut line = format!("{}: {}", address, instruction.opcode().mnemonic());
+        if !operands.is_empty() {
+            line += &format!(" {}", operands.join(", "));
+        }
+        if !reads.is_empty() {
+            let reads: Vec<String> = reads.iter().map(i64::to_string).collect();
+            line += &format!(" | read {}", reads.join(", "));
+        }
+        for (address, value) in writes {
+            line += &format!(" | write [{}]={}", address, value);
+        }
+        if let Some(relative_base) = relative_base {
+            line += &format!(" | rb={}", relative_base);
+        }
+        writeln!(self.sink, "{}", line)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Divergence {
+    pub line: usize,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+// compares two traces line by line, returning the first line where they differ
+pub fn first_divergence<A: BufRead, B: BufRead>(
+    left: A,
+    right: B,
+) -> io::Result<Option<Divergence>> {
+    let mut left = left.lines();
+    let mut right = right.lines();
+    let mut line = 0;
+    loop {
+        line += 1;
+        let (a, b) = (left.next().transpose()?, right.next().transpose()?);
+        if a.is_none() && b.is_none() {
+            return Ok(None);
+        }
+        if a != b {
+            return Ok(Some(Divergence {
+                line,
+                left: a,
+                right: b,
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+    use crate::intcode::Computer;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    fn program() -> Vec<i64> {
+        assemble(
+            "
+                arb #20
+                in rb-2
+                mul rb-2, #3, x
+                out x
+                hlt
+            x: db 0
+            ",
+        )
+        .unwrap()
+    }
+
+    fn trace(input: i64, tracer: impl Fn(SharedBuffer) -> Tracer) -> String {
+        let buffer = SharedBuffer::default();
+        let mut computer = Computer::new(&program(), &[input]);
+        computer.set_tracer(tracer(buffer.clone()));
+        computer.by_ref().for_each(drop);
+        buffer.contents()
+    }
+
+    #[test]
+    fn test_trace() {
+        assert_eq!(
+            trace(7, Tracer::new),
+            concat!(
+                "0: arb #20 | read 20 | rb=20\n",
+                "2: in rb-2 | write [18]=7\n",
+                "4: mul rb-2, #3, 11 | read 7, 3 | write [11]=21\n",
+                "8: out 11 | read 21\n",
+                "10: hlt\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_trace_filters() {
+        assert_eq!(
+            trace(7, |buffer| Tracer::new(buffer).with_addresses(2..9)),
+            concat!(
+                "2: in rb-2 | write [18]=7\n",
+                "4: mul rb-2, #3, 11 | read 7, 3 | write [11]=21\n",
+                "8: out 11 | read 21\n",
+            )
+        );
+        assert_eq!(
+            trace(7, |buffer| Tracer::new(buffer)
+                .with_opcodes(&[OpCode::Output, OpCode::RelativeBaseOffset])),
+            concat!("0: arb #20 | read 20 | rb=20\n", "8: out 11 | read 21\n")
+        );
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let (a, b) = (trace(7, Tracer::new), trace(8, Tracer::new));
+        assert_eq!(
+            first_divergence(a.as_bytes(), b.as_bytes()).unwrap(),
+            Some(Divergence {
+                line: 2,
+                left: Some("2: in rb-2 | write [18]=7".to_string()),
+                right: Some("2: in rb-2 | write [18]=8".to_string()),
+            })
+        );
+        assert_eq!(first_divergence(a.as_bytes(), a.as_bytes()).unwrap(), None);
+        assert_eq!(
+            first_divergence(a.as_bytes(), &a.as_bytes()[..a.len() - "10: hlt\n".len()]).unwrap(),
+            Some(Divergence {
+                line: 5,
+                left: Some("10: hlt".to_string()),
+                right: None,
+            })
+        );
+    }
+}
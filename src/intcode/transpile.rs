@@ -0,0 +1,472 @@
+use std::fmt::Write;
+use std::marker::PhantomData;
+
+use super::analysis::analyze;
+use super::device::{Io, Queue};
+use super::disasm::{Line, Operand};
+use super::{Computer, ComputerError, OpCode, ParameterMode, State};
+
+const PROGRAM_WIDTH: usize = 16;
+
+// a program translated to rust ahead of time by transpile(). execute runs compiled code until
+// the program stops, or returns None when it reaches an address that has to be interpreted
+pub trait Compiled {
+    const PROGRAM: &'static [i64];
+    // address and length of every compiled instruction
+    const INSTRUCTIONS: &'static [(usize, usize)];
+
+    fn execute(machine: &mut Machine) -> Result<Option<State>, ComputerError>;
+}
+
+// the state compiled code runs against. it is an ordinary computer underneath, so the
+// interpreter can pick up wherever the compiled code leaves off
+#[derive(Debug)]
+pub struct Machine {
+    computer: Computer,
+    program: &'static [i64],
+    // the length of the instruction compiled at each address, 0 if there isn't one
+    compiled: Vec<usize>,
+}
+
+impl Machine {
+    fn new<P: Compiled>(input: &[i64]) -> Self {
+        let mut compiled = vec![0; P::PROGRAM.len()];
+        for &(address, len) in P::INSTRUCTIONS {
+            compiled[address] = len;
+        }
+        Machine {
+            computer: Computer::with_memory(P::PROGRAM, input),
+            program: P::PROGRAM,
+            compiled,
+        }
+    }
+    // the address compiled code should run from next, if there is an instruction compiled there
+    // and its cells still hold what was compiled. memory can be written by the interpreter or
+    // through computer_mut as well as by compiled code, so the cells are checked every time
+    pub fn pos(&self) -> Option<i64> {
+        let pos = self.computer.pos;
+        let len = *self.compiled.get(pos as usize)?;
+        let unchanged = (pos..pos + len as i64)
+            .all(|address| self.computer.peek(address) == self.program[address as usize]);
+        Some(pos).filter(|_| len > 0 && unchanged)
+    }
+    pub fn jump(&mut self, address: i64) {
+        self.computer.pos = address;
+    }
+    pub fn relative_base(&self) -> i64 {
+        self.computer.relative_base
+    }
+    pub fn offset_relative_base(&mut self, offset: i64) {
+        self.computer.relative_base = self.computer.relative_base.wrapping_add(offset);
+    }
+    // an address relative to the base, wrapping like the interpreter
+    pub fn relative(&self, offset: i64) -> i64 {
+        self.computer.relative_base.wrapping_add(offset)
+    }
+    // arithmetic wraps unless the computer checks it, the same as when it is interpreted
+    pub fn add(&self, a: i64, b: i64) -> Result<i64, ComputerError> {
+        self.computer
+            .arithmetic(a.checked_add(b), || a.wrapping_add(b))
+    }
+    pub fn mul(&self, a: i64, b: i64) -> Result<i64, ComputerError> {
+        self.computer
+            .arithmetic(a.checked_mul(b), || a.wrapping_mul(b))
+    }
+    pub fn get(&self, address: i64) -> Result<i64, ComputerError> {
+        if address < 0 {
+            return Err(ComputerError::NegativeAddress { address });
+        }
+        Ok(self.computer.peek(address))
+    }
+    pub fn set(&mut self, address: i64, value: i64) -> Result<(), ComputerError> {
+        if address < 0 {
+            return Err(ComputerError::NegativeAddress { address });
+        }
+        self.computer.store(address, value);
+        Ok(())
+    }
+    // stores the next input at address, returns false if there is none yet
+    pub fn input(&mut self, address: i64) -> Result<bool, ComputerError> {
+        if address < 0 {
+            return Err(ComputerError::NegativeAddress { address });
+        }
+        match Io::input(&mut Queue, &mut self.computer) {
+            Some(value) => self.set(address, value).map(|_| true),
+            None => Ok(false),
+        }
+    }
+}
+
+// runs a compiled program with the same interface as a computer. only the instructions that
+// fall back to the interpreter count towards its stats and budget
+#[derive(Debug)]
+pub struct Transpiled<P: Compiled> {
+    machine: Machine,
+    program: PhantomData<P>,
+}
+
+impl<P: Compiled> Transpiled<P> {
+    pub fn new(input: &[i64]) -> Self {
+        Transpiled {
+            machine: Machine::new::<P>(input),
+            program: PhantomData,
+        }
+    }
+    pub fn reset(&mut self) {
+        self.machine = Machine::new::<P>(&[]);
+    }
+    pub fn add_input(&mut self, input: &[i64]) {
+        self.machine.computer.add_input(input);
+    }
+    pub fn computer(&self) -> &Computer {
+        &self.machine.computer
+    }
+    pub fn computer_mut(&mut self) -> &mut Computer {
+        &mut self.machine.computer
+    }
+    pub fn run(&mut self) -> Result<State, ComputerError> {
+        loop {
+            if let Some(state) = P::execute(&mut self.machine)? {
+                return Ok(state);
+            }
+            if let Some(state) = self.machine.computer.step()? {
+                return Ok(state);
+            }
+        }
+    }
+}
+
+impl<P: Compiled> Iterator for Transpiled<P> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        self.run().ok().and_then(State::output)
+    }
+}
+
+fn target(operand: &Operand) -> String {
+    match operand.mode {
+        ParameterMode::Relative => format!("m.relative({})", operand.value),
+        _ => operand.value.to_string(),
+    }
+}
+
+fn read(operand: &Operand) -> String {
+    match operand.mode {
+        ParameterMode::Immediate => operand.value.to_string(),
+        _ => format!("m.get({})?", target(operand)),
+    }
+}
+
+fn statements(address: i64, opcode: OpCode, operands: &[Operand]) -> Vec<String> {
+    let next = address + 1 + operands.len() as i64;
+    let binary = |expression: &str| {
+        vec![
+            format!("let a = {};", read(&operands[0])),
+            format!("let b = {};", read(&operands[1])),
+            format!("m.set({}, {})?;", target(&operands[2]), expression),
+            format!("m.jump({});", next),
+        ]
+    };
+    // a constant condition always goes the same way
+    let branch = |condition: &str, jumps: fn(i64) -> bool| match operands[0].mode {
+        ParameterMode::Immediate if jumps(operands[0].value) => {
+            vec![format!("m.jump({});", read(&operands[1]))]
+        }
+        ParameterMode::Immediate => vec![format!("m.jump({});", next)],
+        _ => vec![
+            format!("if {} {} {{", read(&operands[0]), condition),
+            format!("    m.jump({});", read(&operands[1])),
+            "} else {".to_string(),
+            format!("    m.jump({});", next),
+            "}".to_string(),
+        ],
+    };
+    match opcode {
+        OpCode::Add => binary("m.add(a, b)?"),
+        OpCode::Multiply => binary("m.mul(a, b)?"),
+        OpCode::LessThan => binary("(a < b) as i64"),
+        OpCode::Equals => binary("(a == b) as i64"),
+        OpCode::JumpIfTrue => branch("!= 0", |value| value != 0),
+        OpCode::JumpIfFalse => branch("== 0", |value| value == 0),
+        OpCode::Input => vec![
+            format!("if !m.input({})? {{", target(&operands[0])),
+            "    return Ok(Some(State::NeedsInput));".to_string(),
+            "}".to_string(),
+            format!("m.jump({});", next),
+        ],
+        OpCode::Output => vec![
+            format!("let a = {};", read(&operands[0])),
+            format!("m.jump({});", next),
+            "return Ok(Some(State::Output(a)));".to_string(),
+        ],
+        OpCode::RelativeBaseOffset => vec![
+            format!("let a = {};", read(&operands[0])),
+            format!("m.jump({});", next),
+            "m.offset_relative_base(a);".to_string(),
+        ],
+        OpCode::Stop => vec!["return Ok(Some(State::Halted));".to_string()],
+    }
+}
+
+// translates every reachable instruction into a match arm over the position. instructions the
+// program rewrites are left to the interpreter, as is anything overwritten at run time
+pub fn transpile(program: &[i64], name: &str) -> String {
+    let analysis = analyze(program);
+    let mut instructions = Vec::new();
+    for line in analysis
+        .blocks
+        .values()
+        .flat_map(|block| block.lines.iter())
+    {
+        if let Line::Code {
+            address,
+            opcode,
+            operands,
+        } = line
+        {
+            let end = address + 1 + operands.len() as i64;
+            let rewritten = analysis
+                .self_modifying
+                .iter()
+                .any(|&(_, target)| (*address..end).contains(&target));
+            if !rewritten {
+                instructions.push((*address, *opcode, operands));
+            }
+        }
+    }
+
+    let mut source = String::new();
+    let mut line = |text: String| writeln!(source, "{}", text).unwrap();
+    line("// generated by intcode_transpile, do not edit".to_string());
+    line("use crate::intcode::transpile::{Compiled, Machine};".to_string());
+    line("use crate::intcode::{ComputerError, State};".to_string());
+    line(String::new());
+    line(format!("pub struct {};", name));
+    line(String::new());
+    line(format!("impl Compiled for {} {{", name));
+    line("    const PROGRAM: &'static [i64] = &[".to_string());
+    for chunk in program.chunks(PROGRAM_WIDTH) {
+        let values: Vec<String> = chunk.iter().map(i64::to_string).collect();
+        line(format!("        {},", values.join(", ")));
+    }
+    line("    ];".to_string());
+    line("    const INSTRUCTIONS: &'static [(usize, usize)] = &[".to_string());
+    for chunk in instructions.chunks(PROGRAM_WIDTH / 2) {
+        let values: Vec<String> = chunk
+            .iter()
+            .map(|(address, _, operands)| format!("({}, {})", address, operands.len() + 1))
+            .collect();
+        line(format!("        {},", values.join(", ")));
+    }
+    line("    ];".to_string());
+    line(String::new());
+    line("    fn execute(m: &mut Machine) -> Result<Option<State>, ComputerError> {".to_string());
+    line("        while let Some(pos) = m.pos() {".to_string());
+    line("            match pos {".to_string());
+    for (address, opcode, operands) in instructions.iter() {
+        line(format!("                {} => {{", address));
+        for statement in statements(*address, *opcode, operands) {
+            line(format!("                    {}", statement));
+        }
+        line("                }".to_string());
+    }
+    line("                _ => break,".to_string());
+    line("            }".to_string());
+    line("        }".to_string());
+    line("        Ok(None)".to_string());
+    line("    }".to_string());
+    line("}".to_string());
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::compiled::{Day09, Day19};
+    use crate::intcode::patch::Patch;
+
+    #[test]
+    fn test_generated_code_is_current() {
+        assert_eq!(
+            transpile(&Computer::load_data(9).unwrap(), "Day09"),
+            include_str!("compiled/day_09.rs")
+        );
+        assert_eq!(
+            transpile(&Computer::load_data(19).unwrap(), "Day19"),
+            include_str!("compiled/day_19.rs")
+        );
+    }
+
+    #[test]
+    fn test_day_9_matches_interpreter() {
+        let program = Computer::load_data(9).unwrap();
+        for &input in [1, 2].iter() {
+            let expected: Vec<i64> = Computer::new(&program, &[input]).collect();
+            let actual: Vec<i64> = Transpiled::<Day09>::new(&[input]).collect();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_day_19_matches_interpreter() {
+        let mut computer = Computer::new(&Computer::load_data(19).unwrap(), &[]);
+        let mut transpiled = Transpiled::<Day19>::new(&[]);
+        for (x, y) in iproduct!(0..50, 0..50) {
+            computer.reset();
+            computer.add_input(&[x, y]);
+            transpiled.reset();
+            transpiled.add_input(&[x, y]);
+            assert_eq!(transpiled.run(), computer.run(), "{}, {}", x, y);
+            assert_eq!(transpiled.run(), computer.run(), "{}, {}", x, y);
+        }
+    }
+
+    #[test]
+    fn test_rewritten_code_is_interpreted() {
+        // the add turns the second output into a halt before it runs
+        let program = vec![1101, 0, 99, 6, 104, 1, 104, 2, 99];
+        let source = transpile(&program, "Patched");
+        assert!(source.contains("                4 => {"));
+        assert!(!source.contains("                6 => {"));
+    }
+
+    // what transpile generates for a program that overwrites the output at 6 through the
+    // relative base, which the analysis can't see coming
+    struct Relative;
+
+    impl Compiled for Relative {
+        const PROGRAM: &'static [i64] = &[109, 10, 21101, 0, 99, -4, 104, 5, 99];
+        const INSTRUCTIONS: &'static [(usize, usize)] = &[(0, 2), (2, 4), (6, 2), (8, 1)];
+
+        fn execute(m: &mut Machine) -> Result<Option<State>, ComputerError> {
+            while let Some(pos) = m.pos() {
+                match pos {
+                    0 => {
+                        let a = 10;
+                        m.jump(2);
+                        m.offset_relative_base(a);
+                    }
+                    2 => {
+                        let a = 0;
+                        let b = 99;
+                        m.set(m.relative(-4), m.add(a, b)?)?;
+                        m.jump(6);
+                    }
+                    6 => {
+                        let a = 5;
+                        m.jump(8);
+                        return Ok(Some(State::Output(a)));
+                    }
+                    8 => {
+                        return Ok(Some(State::Halted));
+                    }
+                    _ => break,
+                }
+            }
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_overwritten_code_is_interpreted() {
+        let source = transpile(Relative::PROGRAM, "Relative");
+        assert!(source.contains("m.set(m.relative(-4), m.add(a, b)?)?;"));
+        assert!(source.contains("                6 => {"));
+        let mut transpiled = Transpiled::<Relative>::new(&[]);
+        assert_eq!(transpiled.run(), Ok(State::Halted));
+        assert_eq!(transpiled.computer().pos(), 6);
+    }
+
+    // what transpile generates for an add that overflows
+    struct Overflow;
+
+    impl Compiled for Overflow {
+        const PROGRAM: &'static [i64] = &[1101, i64::MAX, 1, 7, 4, 7, 99, 0];
+        const INSTRUCTIONS: &'static [(usize, usize)] = &[(0, 4), (4, 2), (6, 1)];
+
+        fn execute(m: &mut Machine) -> Result<Option<State>, ComputerError> {
+            while let Some(pos) = m.pos() {
+                match pos {
+                    0 => {
+                        let a = 9223372036854775807;
+                        let b = 1;
+                        m.set(7, m.add(a, b)?)?;
+                        m.jump(4);
+                    }
+                    4 => {
+                        let a = m.get(7)?;
+                        m.jump(6);
+                        return Ok(Some(State::Output(a)));
+                    }
+                    6 => {
+                        return Ok(Some(State::Halted));
+                    }
+                    _ => break,
+                }
+            }
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_overflow_matches_interpreter() {
+        let source = transpile(Overflow::PROGRAM, "Overflow");
+        assert!(source.contains("m.set(7, m.add(a, b)?)?;"));
+        for &checked in [false, true].iter() {
+            let mut computer = Computer::new(&Overflow::PROGRAM.to_vec(), &[]);
+            computer.set_checked_arithmetic(checked);
+            let mut transpiled = Transpiled::<Overflow>::new(&[]);
+            transpiled.computer_mut().set_checked_arithmetic(checked);
+            let expected = computer.run();
+            assert_eq!(transpiled.run(), expected);
+            assert_eq!(transpiled.computer().pos(), computer.pos());
+        }
+        let mut transpiled = Transpiled::<Overflow>::new(&[]);
+        assert_eq!(transpiled.run(), Ok(State::Output(i64::MIN)));
+        transpiled.reset();
+        transpiled.computer_mut().set_checked_arithmetic(true);
+        assert_eq!(
+            transpiled.run(),
+            Err(ComputerError::Overflow { address: 0 })
+        );
+    }
+
+    // the add at 0 isn't compiled, so the interpreter is what overwrites the output at 4
+    struct Interpreted;
+
+    impl Compiled for Interpreted {
+        const PROGRAM: &'static [i64] = &[1101, 0, 99, 4, 104, 5, 99];
+        const INSTRUCTIONS: &'static [(usize, usize)] = &[(4, 2), (6, 1)];
+
+        fn execute(m: &mut Machine) -> Result<Option<State>, ComputerError> {
+            while let Some(pos) = m.pos() {
+                match pos {
+                    4 => {
+                        let a = 5;
+                        m.jump(6);
+                        return Ok(Some(State::Output(a)));
+                    }
+                    6 => {
+                        return Ok(Some(State::Halted));
+                    }
+                    _ => break,
+                }
+            }
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_interpreter_writes_are_interpreted() {
+        let mut transpiled = Transpiled::<Interpreted>::new(&[]);
+        assert_eq!(transpiled.run(), Ok(State::Halted));
+        assert_eq!(transpiled.computer().pos(), 4);
+        // and the same for writes made from outside
+        let mut transpiled = Transpiled::<Interpreted>::new(&[]);
+        let patch = Patch::new("out").set(0, 1105).set(1, 1).set(2, 4).set(5, 8);
+        transpiled.computer_mut().apply_patch(&patch).unwrap();
+        assert_eq!(transpiled.run(), Ok(State::Output(8)));
+        assert_eq!(transpiled.run(), Ok(State::Halted));
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use num::traits::{CheckedAdd, CheckedMul, ToPrimitive};
+use num::BigInt;
+
+use super::{parse_words, ComputerError, Instruction, OpCode, ParameterMode, State};
+
+// anything an intcode cell can hold. arithmetic that doesn't fit the word fails with
+// ComputerError::Overflow, so i64 words give checked arithmetic and big integers never overflow
+pub trait Word:
+    Clone + fmt::Debug + Ord + From<i64> + FromStr + CheckedAdd + CheckedMul + ToPrimitive
+{
+}
+
+impl<W> Word for W where
+    W: Clone + fmt::Debug + Ord + From<i64> + FromStr + CheckedAdd + CheckedMul + ToPrimitive
+{
+}
+
+// a computer that runs on arbitrary words. addresses and the relative base still have to fit
+// in an i64, only the values stored in memory can grow
+#[derive(Debug, Clone)]
+pub struct WideComputer<W: Word> {
+    original_program: Vec<W>,
+    memory: HashMap<i64, W>,
+    pub input_queue: VecDeque<W>,
+    pos: i64,
+    relative_base: i64,
+}
+
+pub type BigComputer = WideComputer<BigInt>;
+
+impl<W: Word> WideComputer<W> {
+    pub fn new(program: &[W], input: &[W]) -> Self {
+        let mut computer = WideComputer {
+            original_program: program.to_vec(),
+            memory: HashMap::new(),
+            input_queue: VecDeque::new(),
+            pos: 0,
+            relative_base: 0,
+        };
+        computer.reset();
+        computer.add_input(input);
+        computer
+    }
+    pub fn from_program(program: &[i64], input: &[i64]) -> Self {
+        let words = |values: &[i64]| -> Vec<W> { values.iter().map(|&x| W::from(x)).collect() };
+        WideComputer::new(&words(program), &words(input))
+    }
+    // like Computer::parse_program, but without limiting the size of the numbers
+    pub fn parse_program(text: &str) -> Result<Vec<W>, ComputerError> {
+        parse_words(text)
+    }
+    pub fn reset(&mut self) {
+        self.memory = (0..).zip(self.original_program.iter().cloned()).collect();
+        self.input_queue.clear();
+        self.pos = 0;
+        self.relative_base = 0;
+    }
+    pub fn add_input(&mut self, input: &[W]) {
+        self.input_queue.extend(input.iter().cloned());
+    }
+    pub fn peek(&self, address: i64) -> W {
+        self.memory
+            .get(&address)
+            .cloned()
+            .unwrap_or_else(|| W::from(0))
+    }
+    pub fn pos(&self) -> i64 {
+        self.pos
+    }
+    fn address(&self, word: &W) -> Result<i64, ComputerError> {
+        word.to_i64()
+            .ok_or(ComputerError::AddressOutOfRange { address: self.pos })
+    }
+    fn load(&self, address: i64) -> Result<W, ComputerError> {
+        if address < 0 {
+            return Err(ComputerError::NegativeAddress { address });
+        }
+        Ok(self.peek(address))
+    }
+    fn get_value(&self, offset: i64, mode: ParameterMode) -> Result<W, ComputerError> {
+        let a = self.load(self.pos + offset)?;
+        match mode {
+            ParameterMode::Position => self.load(self.address(&a)?),
+            ParameterMode::Immediate => Ok(a),
+            ParameterMode::Relative => {
+                self.load(self.relative_base.wrapping_add(self.address(&a)?))
+            }
+        }
+    }
+    fn write_address(&self, offset: i64, mode: ParameterMode) -> Result<i64, ComputerError> {
+        let a = self.address(&self.load(self.pos + offset)?)?;
+        let address = match mode {
+            ParameterMode::Immediate => {
+                return Err(ComputerError::ImmediateWrite { address: self.pos })
+            }
+            ParameterMode::Position => a,
+            ParameterMode::Relative => a.wrapping_add(self.relative_base),
+        };
+        if address < 0 {
+            return Err(ComputerError::NegativeAddress { address });
+        }
+        Ok(address)
+    }
+    fn overflow<T>(&self, value: Option<T>) -> Result<T, ComputerError> {
+        value.ok_or(ComputerError::Overflow { address: self.pos })
+    }
+    // executes one instruction, returns None if execution can simply continue
+    pub fn step(&mut self) -> Result<Option<State<W>>, ComputerError> {
+        // a cell too big for an i64 can't be a valid instruction either
+        let code = self.load(self.pos)?.to_i64().unwrap_or(-1);
+        let instruction = Instruction::decode(code, self.pos)?;
+        let [a, b, c] = instruction.modes();
+        let zero = W::from(0);
+        match instruction.opcode() {
+            OpCode::Add | OpCode::Multiply | OpCode::LessThan | OpCode::Equals => {
+                let (x, y) = (self.get_value(1, a)?, self.get_value(2, b)?);
+                let value = match instruction.opcode() {
+                    OpCode::Add => self.overflow(x.checked_add(&y))?,
+                    OpCode::Multiply => self.overflow(x.checked_mul(&y))?,
+                    OpCode::LessThan => W::from((x < y) as i64),
+                    _ => W::from((x == y) as i64),
+                };
+                let address = self.write_address(3, c)?;
+                self.memory.insert(address, value);
+                self.pos += 4;
+            }
+            OpCode::JumpIfTrue | OpCode::JumpIfFalse => {
+                let condition = self.get_value(1, a)? != zero;
+                if condition == (instruction.opcode() == OpCode::JumpIfTrue) {
+                    self.pos = self.address(&self.get_value(2, b)?)?;
+                } else {
+                    self.pos += 3;
+                }
+            }
+            OpCode::Input => {
+                let address = self.write_address(1, a)?;
+                let value = match self.input_queue.pop_front() {
+                    Some(value) => value,
+                    None => return Ok(Some(State::NeedsInput)),
+                };
+                self.memory.insert(address, value);
+                self.pos += 2;
+            }
+            OpCode::Output => {
+                let value = self.get_value(1, a)?;
+                self.pos += 2;
+                return Ok(Some(State::Output(value)));
+            }
+            OpCode::RelativeBaseOffset => {
+                let offset = self.address(&self.get_value(1, a)?)?;
+                let relative_base = self.relative_base.checked_add(offset);
+                self.relative_base = self.overflow(relative_base)?;
+                self.pos += 2;
+            }
+            OpCode::Stop => return Ok(Some(State::Halted)),
+        }
+        Ok(None)
+    }
+    // unlike Computer there is no default input, running out of input always yields
+    pub fn run(&mut self) -> Result<State<W>, ComputerError> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+}
+
+impl<W: Word> Iterator for WideComputer<W> {
+    type Item = W;
+
+    fn next(&mut self) -> Option<W> {
+        self.run().ok().and_then(State::output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::Computer;
+
+    // squares its input twice, which no longer fits in 64 bits for inputs past 2^16
+    const SQUARES: [i64; 14] = [3, 13, 2, 13, 13, 13, 2, 13, 13, 13, 4, 13, 99, 0];
+
+    #[test]
+    fn test_matches_computer() {
+        let program = Computer::load_data(9).unwrap();
+        for &input in [1, 2].iter() {
+            let expected: Vec<i64> = Computer::new(&program, &[input]).collect();
+            let narrow: Vec<i64> = WideComputer::from_program(&program, &[input]).collect();
+            let big: Vec<BigInt> = BigComputer::from_program(&program, &[input]).collect();
+            assert_eq!(narrow, expected);
+            assert_eq!(
+                big,
+                expected.into_iter().map(BigInt::from).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let program = SQUARES.to_vec();
+        assert_eq!(
+            WideComputer::<i64>::from_program(&program, &[1 << 17]).run(),
+            Err(ComputerError::Overflow { address: 6 })
+        );
+        let mut computer = Computer::new(&program, &[1 << 17]);
+        computer.set_checked_arithmetic(true);
+        assert_eq!(computer.run(), Err(ComputerError::Overflow { address: 6 }));
+
+        let mut big = BigComputer::from_program(&program, &[1 << 17]);
+        assert_eq!(big.run(), Ok(State::Output(BigInt::from(1) << 68)));
+        assert_eq!(big.run(), Ok(State::Halted));
+    }
+
+    #[test]
+    fn test_relative_address_wraps() {
+        // rb i64::MAX, then out rb + 1
+        let program = vec![109, i64::MAX, 204, 1, 99];
+        assert_eq!(
+            WideComputer::<i64>::from_program(&program, &[]).run(),
+            Err(ComputerError::NegativeAddress { address: i64::MIN })
+        );
+        assert_eq!(
+            Computer::new(&program, &[]).run(),
+            Err(ComputerError::NegativeAddress { address: i64::MIN })
+        );
+    }
+
+    #[test]
+    fn test_big_programs() {
+        let text = "104,123456789012345678901234567890,\n9,1,99\n";
+        let program = BigComputer::parse_program(text).unwrap();
+        let mut computer = BigComputer::new(&program, &[]);
+        let huge: BigInt = "123456789012345678901234567890".parse().unwrap();
+        assert_eq!(computer.run(), Ok(State::Output(huge)));
+        // the huge number can't be used to move the relative base
+        assert_eq!(
+            computer.run(),
+            Err(ComputerError::AddressOutOfRange { address: 2 })
+        );
+        assert!(BigComputer::parse_program("1,x").is_err());
+    }
+}
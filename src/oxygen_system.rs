@@ -1,12 +1,13 @@
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::ops::{Add, AddAssign, Sub};
 
 use itertools::Itertools;
 use itertools::MinMaxResult;
 use pathfinding::prelude::astar;
 
-use super::intcode::Computer;
+use super::intcode::history::History;
+use super::intcode::{Computer, InputDevice, OutputDevice, State};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum Tile {
@@ -139,7 +140,9 @@ struct Robot {
     position: Coordinate,
     map: HashMap<Coordinate, Tile>,
     sensor: Option<Coordinate>,
-    computer: Computer,
+    // the move the computer is currently answering, and any moves planned after it
+    moving: Option<Direction>,
+    route: VecDeque<Direction>,
     debug_print: bool,
 }
 
@@ -152,8 +155,9 @@ impl Default for Robot {
                 map.insert(Coordinate::default(), Tile::DISCOVERED);
                 map
             },
-            computer: Computer::new(&Computer::load_data(15), &[]),
             sensor: None,
+            moving: None,
+            route: VecDeque::new(),
             debug_print: false,
         }
     }
@@ -187,34 +191,29 @@ impl Robot {
         }
         println!("******************************");
     }
-    fn do_move(&mut self, direction: &Direction) -> bool {
-        self.computer.input_queue.push_back(*direction as i64);
-        let response = self.computer.next().map(Tile::from);
+    fn do_move(&mut self, direction: &Direction, response: Tile) -> bool {
         match response {
-            Some(Tile::DISCOVERED) => {
+            Tile::DISCOVERED => {
                 self.position += direction;
                 self.map.entry(self.position).or_insert(Tile::DISCOVERED);
                 true
             }
-            Some(Tile::SENSOR) => {
+            Tile::SENSOR => {
                 self.position += direction;
                 self.map.entry(self.position).or_insert(Tile::DISCOVERED);
                 self.sensor = Some(self.position);
                 true
             }
-            Some(Tile::WALL) => {
+            Tile::WALL => {
                 self.map
                     .entry(self.position + direction)
                     .or_insert(Tile::WALL);
                 false
             }
-            Some(Tile::EXPLORED) | Some(Tile::ROBOT) | Some(Tile::UNKNOWN) => panic!(
+            Tile::EXPLORED | Tile::ROBOT | Tile::UNKNOWN => panic!(
                 "robot computer should never respond with this tile: {:?}",
                 response
             ),
-            None => {
-                panic!("something terrible happened, got none response back");
-            }
         }
     }
     fn find_path(&self, src: &Coordinate, target: &Coordinate) -> (Vec<Coordinate>, isize) {
@@ -235,64 +234,100 @@ impl Robot {
         )
         .unwrap()
     }
-    fn navigate_path(&mut self, path: Vec<Coordinate>) {
-        assert_eq!(self.position, path[0]);
-        for i in 1..path.len() {
-            let direction = path[i] - path[i - 1];
-            assert!(self.do_move(&direction));
-            assert_eq!(self.position, path[i]);
-        }
-    }
-    fn navigate_to(&mut self, target: &Coordinate) {
+    fn plan_route(&mut self, target: &Coordinate) {
         let (path, _) = self.find_path(&self.position, target);
-        self.navigate_path(path);
+        self.route = path.windows(2).map(|step| step[1] - step[0]).collect();
     }
-    fn explore(&mut self) {
-        loop {
-            let mut nearby_unexplored =
-                Direction::iter().filter(|z| !self.map.contains_key(&(self.position + z)));
-
-            match nearby_unexplored.next() {
-                Some(direction) => {
-                    self.do_move(&direction);
-                    continue;
-                }
-                None => {
-                    self.map.insert(self.position, Tile::EXPLORED);
-                }
-            }
+    // picks the next move, or None once the whole map has been explored
+    fn next_move(&mut self) -> Option<Direction> {
+        if let Some(direction) = self.route.pop_front() {
+            return Some(direction);
+        }
+        let mut nearby_unexplored =
+            Direction::iter().filter(|z| !self.map.contains_key(&(self.position + z)));
+        if let Some(direction) = nearby_unexplored.next() {
+            return Some(direction);
+        }
+        self.map.insert(self.position, Tile::EXPLORED);
 
-            let mut nearby_partially_explored =
-                Direction::iter().filter(|z| match self.map.get(&(self.position + z)) {
-                    Some(Tile::DISCOVERED) => true,
-                    _ => false,
-                });
+        let mut nearby_partially_explored =
+            Direction::iter().filter(|z| match self.map.get(&(self.position + z)) {
+                Some(Tile::DISCOVERED) => true,
+                _ => false,
+            });
+        if let Some(direction) = nearby_partially_explored.next() {
+            return Some(direction);
+        }
 
-            match nearby_partially_explored.next() {
-                Some(direction) => {
-                    self.do_move(&direction);
-                    continue;
-                }
-                None => (),
+        let possible_unexplored = self
+            .map
+            .iter()
+            .find(|&(_, &value)| value == Tile::DISCOVERED)
+            .map(|(&key, _)| key);
+        match possible_unexplored {
+            Some(coord) => {
+                self.plan_route(&coord);
+                self.route.pop_front()
             }
-
-            let mut possible_unexplored = self
-                .map
-                .iter()
-                .filter(|&(_, &value)| value == Tile::DISCOVERED)
-                .map(|(&key, _)| key);
-
-            match possible_unexplored.next() {
-                Some(coord) => self.navigate_to(&coord),
-                None => {
-                    if self.debug_print {
-                        self.print_map();
-                    }
-                    break;
+            None => {
+                if self.debug_print {
+                    self.print_map();
                 }
+                None
             }
         }
     }
+    fn explore(&mut self) {
+        let mut computer = Computer::new(&Computer::load_data(15).unwrap(), &[]);
+        assert_eq!(computer.run_device(self), Ok(State::NeedsInput));
+    }
+    // explores depth first, but rewinds the computer out of every dead end instead of walking
+    // the robot back out of it
+    fn survey(&mut self) {
+        let mut computer = Computer::new(&Computer::load_data(15).unwrap(), &[]);
+        computer.yield_on_empty = true;
+        computer.set_history(History::new());
+        assert_eq!(computer.run(), Ok(State::NeedsInput));
+        self.survey_from(&mut computer);
+    }
+    fn survey_from(&mut self, computer: &mut Computer) {
+        for direction in Direction::iter() {
+            if self.map.contains_key(&(self.position + &direction)) {
+                continue;
+            }
+            let (position, mark) = (self.position, computer.history().unwrap().len());
+            computer.add_input(&[direction as i64]);
+            let response = computer
+                .run()
+                .unwrap()
+                .output()
+                .expect("no response to move");
+            if self.do_move(&direction, Tile::from(response)) {
+                assert_eq!(computer.run(), Ok(State::NeedsInput));
+                self.survey_from(computer);
+                computer.rewind(computer.history().unwrap().len() - mark);
+                // rewinding puts the move back on the queue
+                computer.input_queue.clear();
+                self.position = position;
+            }
+        }
+    }
+}
+
+impl InputDevice for Robot {
+    fn read(&mut self) -> Option<i64> {
+        self.moving = self.next_move();
+        self.moving.map(|direction| direction as i64)
+    }
+}
+
+impl OutputDevice for Robot {
+    fn write(&mut self, value: i64) {
+        let direction = self.moving.take().expect("response without a move");
+        let moved = self.do_move(&direction, Tile::from(value));
+        // planned routes only cross known open tiles
+        assert!(moved || self.route.is_empty());
+    }
 }
 
 fn flood(map: &HashMap<Coordinate, Tile>, source: &Coordinate) -> usize {
@@ -343,8 +378,7 @@ pub fn solve_part_one() -> isize {
 
 pub fn solve_part_two() -> usize {
     let mut robot = Robot::default();
-    robot.debug_print = false;
-    robot.explore();
+    robot.survey();
     assert!(robot.sensor.is_some());
     flood(&robot.map, &robot.sensor.unwrap())
 }
@@ -369,6 +403,26 @@ mod tests {
             .all(|&tile| tile == Tile::EXPLORED || tile == Tile::WALL));
     }
 
+    #[test]
+    fn test_survey_matches_explore() {
+        let walls = |robot: &Robot| -> Vec<(isize, isize, bool)> {
+            let mut tiles: Vec<(isize, isize, bool)> = robot
+                .map
+                .iter()
+                .map(|(coord, &tile)| (coord.x, coord.y, tile == Tile::WALL))
+                .collect();
+            tiles.sort();
+            tiles
+        };
+        let mut explored = Robot::default();
+        explored.explore();
+        let mut surveyed = Robot::default();
+        surveyed.survey();
+        assert_eq!(walls(&surveyed), walls(&explored));
+        assert_eq!(surveyed.sensor, explored.sensor);
+        assert_eq!(surveyed.position, Coordinate::default());
+    }
+
     #[test]
     fn test_flood() {
         let map: HashMap<Coordinate, Tile> = vec![
@@ -1,13 +1,13 @@
 use super::intcode::Computer;
 
 pub fn solve_part_one() -> Vec<i64> {
-    let program = Computer::load_data(9);
+    let program = Computer::load_data(9).unwrap();
     let computer = Computer::new(&program, &[1]);
     computer.collect()
 }
 
 pub fn solve_part_two() -> Vec<i64> {
-    let program = Computer::load_data(9);
+    let program = Computer::load_data(9).unwrap();
     let computer = Computer::new(&program, &[2]);
     computer.collect()
 }
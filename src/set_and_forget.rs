@@ -1,3 +1,5 @@
+use super::intcode::ascii::AsciiComputer;
+use super::intcode::patch::Patch;
 use super::intcode::Computer;
 
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -72,9 +74,9 @@ fn print_image(image: &Vec<Vec<Pixel>>) {
     );
 }
 
-fn get_image(computer: &mut Computer) -> Vec<Vec<Pixel>> {
-    computer
-        .map(Pixel::from)
+fn get_image(text: &str) -> Vec<Vec<Pixel>> {
+    text.chars()
+        .map(|ch| Pixel::from(ch as i64))
         .collect::<Vec<Pixel>>()
         .split(|&x| x == Pixel::Newline)
         .filter(|z| z.len() > 0)
@@ -98,8 +100,8 @@ fn get_intersections(image: &Vec<Vec<Pixel>>) -> Vec<(usize, usize)> {
 }
 
 pub fn solve_part_one() -> usize {
-    let mut computer = Computer::new(&Computer::load_data(17), &[]);
-    let image = get_image(&mut computer);
+    let mut computer = AsciiComputer::new(&Computer::load_data(17).unwrap());
+    let image = get_image(&computer.read().unwrap().text);
     print_image(&image);
     let intersections = get_intersections(&image);
     intersections.into_iter().map(|(x, y)| x * y).sum()
@@ -149,20 +151,24 @@ pub fn solve_part_one() -> usize {
  C: L12 R12 L6
 */
 pub fn solve_part_two() -> i64 {
-    let input: Vec<i64> = concat!(
-        "A,B,A,B,C,B,C,A,C,C\n",
-        "R,12,L,10,L,10\n",
-        "L,6,L,12,R,12,L,4\n",
-        "L,12,R,12,L,6\n",
-        "n\n"
-    )
-    .chars()
-    .map(|x| x as i64)
-    .collect();
-
-    let mut data = Computer::load_data(17);
-    data[0] = 2;
-    Computer::new(&data, &input).last().unwrap()
+    let mut computer = Computer::new(&Computer::load_data(17).unwrap(), &[]);
+    computer
+        .apply_patch(&Patch::new("wake up").set(0, 2))
+        .unwrap();
+    let mut computer = AsciiComputer::from_computer(computer);
+    for (prompt, line) in [
+        ("Main:\n", "A,B,A,B,C,B,C,A,C,C"),
+        ("Function A:\n", "R,12,L,10,L,10"),
+        ("Function B:\n", "L,6,L,12,R,12,L,4"),
+        ("Function C:\n", "L,12,R,12,L,6"),
+        ("Continuous video feed?\n", "n"),
+    ]
+    .iter()
+    {
+        computer.read_until(prompt).unwrap();
+        computer.send(line);
+    }
+    computer.read().unwrap().value().unwrap()
 }
 
 #[cfg(test)]
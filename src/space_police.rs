@@ -1,6 +1,6 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
-use super::intcode::Computer;
+use super::intcode::{Computer, InputDevice, OutputDevice};
 
 #[derive(Eq, PartialEq, Copy, Clone)]
 enum Color {
@@ -86,54 +86,52 @@ impl Position {
 }
 
 struct Robot {
-    computer: Computer,
     position: Position,
     map: Map,
+    // the computer alternates between painting and turning
+    turn_next: bool,
 }
 
 impl Robot {
-    fn new() -> Self {
+    fn new(map: Map) -> Self {
         Robot {
-            computer: Computer::new(&Computer::load_data(11), &[]),
-            map: HashMap::new(),
+            map,
             position: Position {
                 x: 0,
                 y: 0,
                 direction: Direction::up(),
             },
+            turn_next: false,
         }
     }
-    fn input_current_color(&mut self, color: &Color) {
-        self.computer.input_queue.push_back((*color) as i64);
-    }
     fn paint(&mut self) -> &Map {
-        self.last();
+        let mut computer = Computer::new(&Computer::load_data(11).unwrap(), &[]);
+        computer.run_device(self).unwrap();
         &self.map
     }
 }
 
-impl Iterator for Robot {
-    type Item = (isize, isize);
-
-    fn next(&mut self) -> Option<(isize, isize)> {
+impl InputDevice for Robot {
+    fn read(&mut self) -> Option<i64> {
         let current_color = *self
             .map
-            .entry(self.position.as_tuple())
-            .or_insert(Color::BLACK);
-        self.input_current_color(&current_color);
-        let color_to_paint = {
-            if let Some(next_color) = self.computer.next() {
-                Color::from(next_color)
-            } else {
-                return None;
-            }
-        };
-        let turn = Turn::from(self.computer.next().unwrap());
-        self.map.insert(self.position.as_tuple(), color_to_paint);
-        self.position.direction = self.position.direction.turn(&turn);
-        let last_position = self.position.as_tuple();
-        self.position = self.position.next();
-        Some(last_position)
+            .get(&self.position.as_tuple())
+            .unwrap_or(&Color::BLACK);
+        Some(current_color as i64)
+    }
+}
+
+impl OutputDevice for Robot {
+    fn write(&mut self, value: i64) {
+        if self.turn_next {
+            let turn = Turn::from(value);
+            self.position.direction = self.position.direction.turn(&turn);
+            self.position = self.position.next();
+        } else {
+            self.map
+                .insert(self.position.as_tuple(), Color::from(value));
+        }
+        self.turn_next = !self.turn_next;
     }
 }
 
@@ -165,13 +163,11 @@ fn print_map(map: &Map) {
 }
 
 pub fn solve_part_one() -> usize {
-    let robot = Robot::new();
-    robot.collect::<HashSet<(isize, isize)>>().len()
+    Robot::new(HashMap::new()).paint().len()
 }
 
 pub fn solve_part_two() {
-    let mut robot = Robot::new();
-    robot.map.insert((0, 0), Color::WHITE);
+    let mut robot = Robot::new(vec![((0, 0), Color::WHITE)].into_iter().collect());
     let map = robot.paint();
     println!("day 11 part two: ");
     print_map(&map);
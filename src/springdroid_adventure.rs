@@ -1,25 +1,35 @@
+use super::intcode::ascii::{self, AsciiComputer};
+use super::intcode::profile::Profiler;
 use super::intcode::Computer;
 
+const WALK: [&str; 6] = ["OR A J", "AND B J", "AND C J", "NOT J J", "AND D J", "WALK"];
+const RUN: [&str; 9] = [
+    "OR A J", "AND B J", "AND C J", "NOT J J", "AND D J", "OR E T", "OR H T", "AND T J", "RUN",
+];
+
+// runs a springscript with coverage on, to see which parts of the program it exercises
+pub fn profile(script: &[&str]) -> Profiler {
+    let mut computer = Computer::new(&Computer::load_data(21).unwrap(), &ascii::encode(script));
+    computer.set_profiler(Profiler::new().with_coverage());
+    computer.by_ref().for_each(drop);
+    computer.take_profiler().unwrap()
+}
+
+fn run_script(script: &[&str]) -> i64 {
+    let mut computer = AsciiComputer::new(&Computer::load_data(21).unwrap());
+    computer.read().unwrap();
+    computer.send_lines(script);
+    let reply = computer.read().unwrap();
+    print!("{}", reply.text);
+    reply.value().unwrap()
+}
+
 pub fn solve_part_one() -> i64 {
-    let input = Computer::ascii_to_intcode(&vec![
-        "OR A J", "AND B J", "AND C J", "NOT J J", "AND D J", "WALK",
-    ]);
-    let computer = Computer::new(&Computer::load_data(21), &input);
-    let output: Vec<i64> = computer.collect();
-    let ascii = Computer::intcode_to_ascii(&output);
-    print!("{}", ascii);
-    *output.last().unwrap()
+    run_script(&WALK)
 }
 
 pub fn solve_part_two() -> i64 {
-    let input: Vec<i64> = Computer::ascii_to_intcode(&vec![
-        "OR A J", "AND B J", "AND C J", "NOT J J", "AND D J", "OR E T", "OR H T", "AND T J", "RUN",
-    ]);
-    let computer = Computer::new(&Computer::load_data(21), &input);
-    let output = computer.collect();
-    let ascii = Computer::intcode_to_ascii(&output);
-    print!("{}", ascii);
-    *output.last().unwrap()
+    run_script(&RUN)
 }
 
 #[cfg(test)]
@@ -35,4 +45,18 @@ mod tests {
     fn test_solve_part_two() {
         assert_eq!(solve_part_two(), 1147582556);
     }
+
+    #[test]
+    fn test_profile() {
+        let walk = profile(&WALK);
+        let run = profile(&RUN);
+        // jumps too early and falls into a hole, so the code that draws the fall only runs here
+        let jump = profile(&["NOT A J", "WALK"]);
+        let only =
+            |a: &Profiler, b: &Profiler| a.executed().filter(|&x| b.executions(x) == 0).count();
+        assert!(only(&run, &walk) > 0);
+        assert!(only(&jump, &walk) > 0);
+        assert!(only(&walk, &jump) > 0);
+        assert!(run.executed().count() > 0 && run.report().contains("coverage:"));
+    }
 }
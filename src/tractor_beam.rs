@@ -1,9 +1,48 @@
 use std::collections::{HashMap, HashSet};
 
+use super::intcode::compiled::Day19;
+use super::intcode::transpile::{Compiled, Transpiled};
 use super::intcode::Computer;
 
+// the drone program, interpreted or compiled
+trait Drone: Iterator<Item = i64> {
+    fn reset(&mut self);
+    fn add_input(&mut self, input: &[i64]);
+}
+
+impl Drone for Computer {
+    fn reset(&mut self) {
+        Computer::reset(self)
+    }
+    fn add_input(&mut self, input: &[i64]) {
+        Computer::add_input(self, input)
+    }
+}
+
+impl<P: Compiled> Drone for Transpiled<P> {
+    fn reset(&mut self) {
+        Transpiled::reset(self)
+    }
+    fn add_input(&mut self, input: &[i64]) {
+        Transpiled::add_input(self, input)
+    }
+}
+
+// the compiled program is only the checked in input, anything else is interpreted
+fn drone() -> Box<dyn Drone> {
+    let program = Computer::load_data(19).unwrap();
+    if program == Day19::PROGRAM {
+        Box::new(Transpiled::<Day19>::new(&[]))
+    } else {
+        Box::new(Computer::new(&program, &[]))
+    }
+}
+
 pub fn solve_part_one() -> usize {
-    let mut computer = Computer::new(&Computer::load_data(19), &[]);
+    count_affected(drone().as_mut())
+}
+
+fn count_affected(computer: &mut dyn Drone) -> usize {
     let mut affected: HashSet<(i64, i64)> = HashSet::new();
     for y in 0..50 {
         let mut found_beam = false;
@@ -25,7 +64,7 @@ pub fn solve_part_one() -> usize {
 }
 
 fn is_in_tractor_beam(
-    computer: &mut Computer,
+    computer: &mut dyn Drone,
     cache: &mut HashMap<(i64, i64), i64>,
     &(x, y): &(i64, i64),
 ) -> bool {
@@ -39,16 +78,19 @@ fn is_in_tractor_beam(
 }
 
 pub fn solve_part_two() -> i64 {
-    let mut computer = Computer::new(&Computer::load_data(19), &[]);
+    closest_square(drone().as_mut())
+}
+
+fn closest_square(computer: &mut dyn Drone) -> i64 {
     let mut cache: HashMap<(i64, i64), i64> = HashMap::new();
     let mut x = 0;
     let mut y = 100;
     loop {
-        while !is_in_tractor_beam(&mut computer, &mut cache, &(x, y)) {
+        while !is_in_tractor_beam(computer, &mut cache, &(x, y)) {
             x += 1;
         }
 
-        if is_in_tractor_beam(&mut computer, &mut cache, &(x + 99, y - 99)) {
+        if is_in_tractor_beam(computer, &mut cache, &(x + 99, y - 99)) {
             return x * 10_000 + y - 99;
         }
 
@@ -69,4 +111,11 @@ mod tests {
     fn test_solve_part_two() {
         assert_eq!(solve_part_two(), 9791328)
     }
+
+    #[test]
+    fn test_interpreted_matches_compiled() {
+        let mut computer = Computer::new(&Computer::load_data(19).unwrap(), &[]);
+        assert_eq!(count_affected(&mut computer), 154);
+        assert_eq!(closest_square(&mut computer), 9791328);
+    }
 }